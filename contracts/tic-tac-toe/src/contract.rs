@@ -1,8 +1,24 @@
 use crate::errors::ContractError;
-use crate::execution::{try_accept, try_invite, try_play, try_reject};
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::query::{query_game, query_games};
-use crate::state::GAMES_COUNT;
+use crate::execution::migrate_legacy_games;
+use crate::execution::{
+    try_accept, try_accept_open_invite, try_accept_series, try_auto_match, try_cancel_open_invite,
+    try_claim_timeout, try_create_league, try_create_season, try_create_series,
+    try_create_tournament, try_finalize_season, try_fund_house, try_invite, try_join_league,
+    try_join_tournament, try_open_invite, try_play, try_play_house, try_receive, try_receive_nft,
+    try_reject, try_reject_series, try_rematch, try_reveal, try_start_league, try_start_tournament,
+    try_update_config, try_withdraw_fees, try_withdraw_house,
+};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::query::{
+    query_active_games, query_escrow_balance, query_fees, query_game, query_game_moves,
+    query_games, query_house_bankroll, query_leaderboard, query_lobby, query_player_games,
+    query_player_stats, query_rating, query_rematch_history, query_replay_at, query_season,
+    query_season_ranking, query_series, query_series_games, query_standings, query_tournament,
+};
+use crate::state::{
+    Config, HouseConfig, CONFIG, DEFAULT_MAX_OPEN_GAMES_PER_PAIR, DEFAULT_REVEAL_TIMEOUT,
+    GAMES_COUNT, MAX_BPS,
+};
 use cosmwasm_std::to_binary;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
@@ -10,29 +26,117 @@ use cw2::{get_contract_version, set_contract_version};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "tic-tac-toe";
-const CONTRACT_VERSION: &str = "0.1.0";
+const CONTRACT_VERSION: &str = "0.2.0";
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let max_open_games_per_pair = msg
+        .max_open_games_per_pair
+        .unwrap_or(DEFAULT_MAX_OPEN_GAMES_PER_PAIR);
+    if max_open_games_per_pair == 0 {
+        return Err(ContractError::InvalidConfig {
+            reason: String::from("max_open_games_per_pair must be at least 1"),
+        });
+    }
+    let reveal_timeout = msg.reveal_timeout.unwrap_or(DEFAULT_REVEAL_TIMEOUT);
+    if reveal_timeout == 0 {
+        return Err(ContractError::InvalidConfig {
+            reason: String::from("reveal_timeout must be at least 1 second"),
+        });
+    }
+    let fee_bps = msg.fee_bps.unwrap_or_default();
+    if fee_bps > MAX_BPS {
+        return Err(ContractError::InvalidConfig {
+            reason: format!("fee_bps must be at most {}", MAX_BPS),
+        });
+    }
+    let treasury = msg
+        .treasury
+        .map(|treasury| deps.api.addr_validate(&treasury))
+        .transpose()?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            admin: info.sender,
+            max_open_games_per_pair,
+            reveal_timeout,
+            house: HouseConfig::default(),
+            treasury,
+            fee_bps,
+            stake_limits: vec![],
+        },
+    )?;
     GAMES_COUNT.save(deps.storage, &0)?;
     Ok(Response::new().add_attribute("action", "tic-tac-toe"))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigration {
+            reason: format!("cannot migrate from {}", version.contract),
+        });
+    }
+
+    if CONFIG.may_load(deps.storage)?.is_none() {
+        let admin = msg.admin.ok_or_else(|| ContractError::InvalidMigration {
+            reason: format!("an admin is required to migrate from {}", version.version),
+        })?;
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                admin: deps.api.addr_validate(&admin)?,
+                max_open_games_per_pair: DEFAULT_MAX_OPEN_GAMES_PER_PAIR,
+                reveal_timeout: DEFAULT_REVEAL_TIMEOUT,
+                house: HouseConfig::default(),
+                treasury: None,
+                fee_bps: 0,
+                stake_limits: vec![],
+            },
+        )?;
+    }
+    let migrated_games = migrate_legacy_games(deps.storage)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("migrated_games", migrated_games.to_string()))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Invite { coord, opponent } => try_invite(deps, info, coord, opponent),
+        ExecuteMsg::Invite {
+            coord,
+            opponent,
+            ruleset,
+            first_player,
+            commitment,
+        } => try_invite(
+            deps,
+            env,
+            info,
+            coord,
+            opponent,
+            ruleset,
+            first_player,
+            commitment,
+            None,
+            None,
+        ),
         ExecuteMsg::Reject {
             as_host,
             opponent,
@@ -42,21 +146,181 @@ pub fn execute(
             coord,
             host,
             game_id,
-        } => try_accept(deps, info, coord, host, game_id),
+            nonce,
+        } => try_accept(deps, env, info, coord, host, game_id, nonce, None, None),
+        ExecuteMsg::Reveal { game_id, secret } => try_reveal(deps, info, game_id, secret),
+        ExecuteMsg::ClaimTimeout { game_id } => try_claim_timeout(deps, env, info, game_id),
+        ExecuteMsg::Rematch { game_id } => try_rematch(deps, info, game_id, None),
+        ExecuteMsg::CreateSeries {
+            opponent,
+            best_of,
+            ruleset,
+            move_timeout,
+        } => try_create_series(deps, info, opponent, best_of, ruleset, move_timeout),
+        ExecuteMsg::AcceptSeries { series_id } => try_accept_series(deps, env, info, series_id),
+        ExecuteMsg::RejectSeries { series_id } => try_reject_series(deps, info, series_id),
+        ExecuteMsg::OpenInvite {
+            ruleset,
+            first_player,
+            allow_list,
+        } => try_open_invite(deps, info, ruleset, first_player, allow_list),
+        ExecuteMsg::AcceptOpenInvite { invite_id, coord } => {
+            try_accept_open_invite(deps, env, info, invite_id, coord)
+        }
+        ExecuteMsg::CancelOpenInvite { invite_id } => try_cancel_open_invite(deps, info, invite_id),
+        ExecuteMsg::AutoMatch { stake } => try_auto_match(deps, env, info, stake),
+        ExecuteMsg::CreateTournament {
+            entry_fee,
+            max_players,
+            start_time,
+            payout_split,
+            ruleset,
+            move_timeout,
+        } => try_create_tournament(
+            deps,
+            info,
+            entry_fee,
+            max_players,
+            start_time,
+            payout_split,
+            ruleset,
+            move_timeout,
+        ),
+        ExecuteMsg::JoinTournament { tournament_id } => {
+            try_join_tournament(deps, env, info, tournament_id)
+        }
+        ExecuteMsg::StartTournament { tournament_id } => {
+            try_start_tournament(deps, env, tournament_id)
+        }
+        ExecuteMsg::CreateLeague {
+            entry_fee,
+            max_players,
+            start_time,
+            double_round_robin,
+            points,
+            payout_split,
+            ruleset,
+            move_timeout,
+        } => try_create_league(
+            deps,
+            info,
+            entry_fee,
+            max_players,
+            start_time,
+            double_round_robin,
+            points,
+            payout_split,
+            ruleset,
+            move_timeout,
+        ),
+        ExecuteMsg::JoinLeague { league_id } => try_join_league(deps, env, info, league_id),
+        ExecuteMsg::StartLeague { league_id } => try_start_league(deps, env, league_id),
+        ExecuteMsg::CreateSeason {
+            start_time,
+            end_time,
+            payout_split,
+            points,
+        } => try_create_season(deps, info, start_time, end_time, payout_split, points),
+        ExecuteMsg::FinalizeSeason { season_id } => try_finalize_season(deps, env, season_id),
+        ExecuteMsg::PlayHouse { coord, stake } => try_play_house(deps, env, info, coord, stake),
+        ExecuteMsg::FundHouse {} => try_fund_house(deps, info),
+        ExecuteMsg::WithdrawHouse { amount } => try_withdraw_house(deps, info, amount),
         ExecuteMsg::Play {
             as_host,
             coord,
             opponent,
             game_id,
-        } => try_play(deps, info, as_host, coord, opponent, game_id),
+            symbol,
+        } => try_play(deps, env, info, as_host, coord, opponent, game_id, symbol),
+        ExecuteMsg::UpdateConfig {
+            max_open_games_per_pair,
+            reveal_timeout,
+            house,
+            treasury,
+            fee_bps,
+            stake_limits,
+        } => try_update_config(
+            deps,
+            info,
+            max_open_games_per_pair,
+            reveal_timeout,
+            house,
+            treasury,
+            fee_bps,
+            stake_limits,
+        ),
+        ExecuteMsg::WithdrawFees {} => try_withdraw_fees(deps, info),
+        ExecuteMsg::Receive(wrapper) => try_receive(deps, env, info, wrapper),
+        ExecuteMsg::ReceiveNft(wrapper) => try_receive_nft(deps, env, info, wrapper),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetContractVersion {} => to_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
         QueryMsg::Game { key, game_id } => to_binary(&query_game(deps, key, game_id)?),
-        QueryMsg::Games { status } => to_binary(&query_games(deps, status)?),
+        QueryMsg::Games {
+            status,
+            player,
+            start_after,
+            limit,
+        } => to_binary(&query_games(deps, status, player, start_after, limit)?),
+        QueryMsg::PlayerGames {
+            address,
+            role,
+            status,
+            start_after,
+            limit,
+        } => to_binary(&query_player_games(
+            deps,
+            address,
+            role,
+            status,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ActiveGames { player, opponent } => {
+            to_binary(&query_active_games(deps, player, opponent)?)
+        }
+        QueryMsg::GameMoves { game_id } => to_binary(&query_game_moves(deps, game_id)?),
+        QueryMsg::RematchHistory { game_id } => to_binary(&query_rematch_history(deps, game_id)?),
+        QueryMsg::Series { id } => to_binary(&query_series(deps, id)?),
+        QueryMsg::SeriesGames { id } => to_binary(&query_series_games(deps, id)?),
+        QueryMsg::Tournament { id } => to_binary(&query_tournament(deps, id)?),
+        QueryMsg::Standings { league_id } => to_binary(&query_standings(deps, league_id)?),
+        QueryMsg::Season { id } => to_binary(&query_season(deps, env.block.time, id)?),
+        QueryMsg::SeasonRanking {
+            season_id,
+            start_after,
+            limit,
+        } => to_binary(&query_season_ranking(
+            deps,
+            env.block.time,
+            season_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::HouseBankroll {} => to_binary(&query_house_bankroll(deps)?),
+        QueryMsg::Fees {} => to_binary(&query_fees(deps)?),
+        QueryMsg::EscrowBalance {} => {
+            to_binary(&query_escrow_balance(deps, &env.contract.address)?)
+        }
+        QueryMsg::Rating { address } => to_binary(&query_rating(deps, address)?),
+        QueryMsg::PlayerStats { address } => to_binary(&query_player_stats(deps, address)?),
+        QueryMsg::Leaderboard { start_after, limit } => {
+            to_binary(&query_leaderboard(deps, start_after, limit)?)
+        }
+        QueryMsg::Lobby {
+            denom,
+            min_stake,
+            start_after,
+            limit,
+        } => to_binary(&query_lobby(deps, denom, min_stake, start_after, limit)?),
+        QueryMsg::ReplayAt {
+            game_id,
+            move_index,
+        } => to_binary(&query_replay_at(deps, game_id, move_index)?),
     }
 }
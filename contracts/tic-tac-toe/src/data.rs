@@ -1,12 +1,129 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Coin};
+use cw20::Cw20Coin;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::Game;
+use crate::state::{
+    Game, LeagueStanding, Move, OpenInvite, PlayerStats, PlayerSymbol, Season, Series, Status,
+    Tournament,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct GameResponse {
+    pub game_id: u64,
     pub game: Game,
     pub host: Addr,
     pub opponent: Addr,
 }
+
+impl GameResponse {
+    pub fn new(game_id: u64, game: Game) -> GameResponse {
+        GameResponse {
+            game_id,
+            host: game.host.clone(),
+            opponent: game.opponent.clone(),
+            game,
+        }
+    }
+}
+
+/**
+    Games of a single player grouped by what the player
+    is expected to do next:
+    - invitations: invites addressed to the player waiting to be accepted or rejected,
+    - my_turn: games in progress where the player has to move,
+    - waiting: own invites and games in progress waiting for the other player,
+    - history: completed and rejected games, most recent first.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PlayerGamesResponse {
+    pub invitations: Vec<GameResponse>,
+    pub my_turn: Vec<GameResponse>,
+    pub waiting: Vec<GameResponse>,
+    pub history: Vec<GameResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeriesResponse {
+    pub series_id: u64,
+    pub series: Series,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentResponse {
+    pub tournament_id: u64,
+    pub tournament: Tournament,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandingsResponse {
+    pub league_id: u64,
+    pub status: Status,
+    pub winner: Option<Addr>,
+    pub games_left: u32,
+    /**
+        From the first to the last place.
+    */
+    pub standings: Vec<LeagueStanding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RatingResponse {
+    pub address: Addr,
+    pub rating: u32,
+    pub games: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlayerStatsResponse {
+    pub address: Addr,
+    pub stats: PlayerStats,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeasonResponse {
+    pub season_id: u64,
+    pub season: Season,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeasonRankingResponse {
+    pub season_id: u64,
+    pub standings: Vec<LeagueStanding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowBalanceResponse {
+    pub escrowed: Vec<Coin>,
+    pub balance: Vec<Coin>,
+
+    /**
+        Coins held by the contract beyond the escrowed ones.
+    */
+    pub surplus: Vec<Coin>,
+
+    /**
+        Escrowed coins the contract does not hold.
+    */
+    pub shortfall: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeesResponse {
+    pub treasury: Addr,
+    pub native: Vec<Coin>,
+    pub tokens: Vec<Cw20Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OpenInviteResponse {
+    pub invite_id: u64,
+    pub invite: OpenInvite,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReplayResponse {
+    pub move_index: u32,
+    pub last_move: Move,
+    pub board: Vec<Vec<Option<PlayerSymbol>>>,
+}
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::{Addr, Coin, StdError, Timestamp, Uint128};
 use thiserror::Error;
 
 use super::state::Coord;
@@ -31,9 +31,108 @@ pub enum ContractError {
     #[error("You already played this turn. Wait for '{{second_player}}' to play its turn.")]
     TurnAlreadyPlayed { second_player: String },
 
-    #[error("Invalid coordinate x={{coord.x}} y={{coord.y}}. Coordinates must be between 0 and 2")]
+    #[error("Invalid coordinate x={} y={}. Coordinates must be inside the board", coord.x, coord.y)]
     InvalidCoord { coord: Coord },
 
+    #[error("x={} and y={} are not on a sub-board open for this move", coord.x, coord.y)]
+    SubBoardNotPlayable { coord: Coord },
+
+    #[error("Wild games require choosing the symbol to place")]
+    SymbolRequired {},
+
+    #[error("The symbol to place can only be chosen on wild games")]
+    SymbolNotAllowed {},
+
+    #[error("Invalid ruleset: board size must be between {min} and {max}, and the win length between {min} and the board size")]
+    InvalidRuleset { min: u8, max: u8 },
+
+    #[error("Only the player moving first can play a move before the game starts")]
+    FirstMoveNotAllowed {},
+
+    #[error("A coin flip can only decide the first player of random games")]
+    CoinFlipNotAllowed {},
+
+    #[error("A random first player must be decided by a coin flip")]
+    CoinFlipRequired {},
+
+    #[error("The commitment must be the {length} bytes SHA-256 hash of the secret")]
+    InvalidCommitment { length: usize },
+
+    #[error("Accepting a coin flip game requires a nonce")]
+    NonceRequired {},
+
+    #[error("The secret must match the commitment and be at least {min_length} bytes long")]
+    InvalidSecret { min_length: usize },
+
+    #[error("The host can reveal its secret until {deadline}")]
+    RevealNotExpired { deadline: Timestamp },
+
+    #[error("The player on turn can move until {deadline}")]
+    MoveNotExpired { deadline: Timestamp },
+
+    #[error("The move timeout must be at least 1 second")]
+    InvalidMoveTimeout {},
+
+    #[error("Game {game_id} already has a rematch")]
+    RematchAlreadyStarted { game_id: u64 },
+
+    #[error("A series must have an odd amount of games between 1 and {max}")]
+    InvalidSeries { max: u8 },
+
+    #[error("Open invites require a stake of exactly one coin")]
+    InvalidStake {},
+
+    #[error("Stakes must be of a single denom")]
+    MultipleStakeDenoms {},
+
+    #[error("A stake is required")]
+    StakeRequired {},
+
+    #[error("{denom} is not allowed as a stake")]
+    StakeDenomNotAllowed { denom: String },
+
+    #[error("The stake must be of at least {min}")]
+    StakeTooLow { min: Coin },
+
+    #[error("The stake must be of at most {max}")]
+    StakeTooHigh { max: Coin },
+
+    #[error("Invalid tournament: {reason}")]
+    InvalidTournament { reason: String },
+
+    #[error("You already joined this competition")]
+    AlreadyJoined {},
+
+    #[error("The tournament cannot start before {start_time}")]
+    TournamentNotStarted { start_time: Timestamp },
+
+    #[error("Invalid league: {reason}")]
+    InvalidLeague { reason: String },
+
+    #[error("The league cannot start before {start_time}")]
+    LeagueNotStarted { start_time: Timestamp },
+
+    #[error("Invalid season: {reason}")]
+    InvalidSeason { reason: String },
+
+    #[error("The season cannot be finalized before {end_time}")]
+    SeasonNotEnded { end_time: Timestamp },
+
+    #[error("The house accepts stakes of up to {max}")]
+    HouseStakeTooHigh { max: Uint128 },
+
+    #[error("The house bankroll only has {available} available")]
+    InsufficientBankroll { available: Uint128 },
+
+    #[error("Invalid NFT wager: {reason}")]
+    InvalidNftWager { reason: String },
+
     #[error("The funds you send must be equal to the prize of the game")]
     InvalidReceivedFunds {},
+
+    #[error("Invalid config: {reason}")]
+    InvalidConfig { reason: String },
+
+    #[error("Invalid migration: {reason}")]
+    InvalidMigration { reason: String },
 }
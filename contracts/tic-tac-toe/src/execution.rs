@@ -1,39 +1,120 @@
+use std::slice;
+
 #[cfg(not(feature = "library"))]
-use cosmwasm_std::{Addr, BankMsg, DepsMut, MessageInfo, Response};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, DepsMut, Env, MessageInfo,
+    Order, Response, StdError, StdResult, Storage, Timestamp, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
 
 use crate::errors::ContractError;
-use crate::state::{Coord, Game, Status, GAMES, GAMES_COUNT};
+use crate::house;
+use crate::msg::{ReceiveMsg, ReceiveNftMsg};
+use crate::state::{
+    games, normalize_coins, open_invites, pair_key, ratings, season_scores, CoinFlip, Coord,
+    FirstPlayer, Game, HouseConfig, HouseDifficulty, League, LeaguePoints, LeagueStanding, Move,
+    Nft, NftWager, OpenInvite, PlayerStats, PlayerSymbol, Ruleset, Season, SeasonScore, Series,
+    StakeLimit, Status, Tournament, TournamentMatch, COMMITMENT_LENGTH, CONFIG,
+    DEFAULT_MOVE_TIMEOUT, FEES, GAMES_COUNT, HOUSE_BANKROLL, LEAGUES, LEAGUES_COUNT, LEGACY_GAMES,
+    MAX_AUTO_MATCH_SCAN, MAX_BPS, MAX_LEAGUE_PLAYERS, MAX_SEASON_WINNERS, MAX_SERIES_GAMES,
+    MAX_TOURNAMENT_DRAWS, MAX_TOURNAMENT_PLAYERS, MIN_BOARD_SIZE, MIN_SECRET_LENGTH, MOVES,
+    OPEN_GAMES, OPEN_INVITES_COUNT, PLAYER_STATS, REMATCHES, SEASONS, SEASONS_COUNT, SERIES,
+    SERIES_COUNT, TOKEN_FEES, TOURNAMENTS, TOURNAMENTS_COUNT,
+};
 
+#[allow(clippy::too_many_arguments)]
 pub fn try_invite(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    coord: Coord,
+    coord: Option<Coord>,
     opponent: String,
+    ruleset: Option<Ruleset>,
+    first_player: Option<FirstPlayer>,
+    commitment: Option<Binary>,
+    token: Option<Addr>,
+    nft_wager: Option<NftWager>,
 ) -> Result<Response, ContractError> {
     let opponent_address = deps.api.addr_validate(&opponent)?;
-    if !coord.is_valid() {
-        return Err(ContractError::InvalidCoord { coord });
+    let ruleset = ruleset.unwrap_or_default();
+    let first_player = first_player.unwrap_or_default();
+    if !ruleset.is_valid() {
+        return Err(ContractError::InvalidRuleset {
+            min: MIN_BOARD_SIZE,
+            max: ruleset.max_board_size(),
+        });
+    }
+    if let Some(coord) = coord {
+        if first_player != FirstPlayer::Host {
+            return Err(ContractError::FirstMoveNotAllowed {});
+        } else if !ruleset.is_valid_coord(coord) {
+            return Err(ContractError::InvalidCoord { coord });
+        }
+    }
+    match &commitment {
+        Some(_) if first_player != FirstPlayer::Random => {
+            return Err(ContractError::CoinFlipNotAllowed {});
+        }
+        Some(commitment) if commitment.len() != COMMITMENT_LENGTH => {
+            return Err(ContractError::InvalidCommitment {
+                length: COMMITMENT_LENGTH,
+            });
+        }
+        None if first_player == FirstPlayer::Random => {
+            return Err(ContractError::CoinFlipRequired {});
+        }
+        _ => {}
     }
 
     if opponent_address == info.sender {
         return Err(ContractError::CannotStartGame {});
     }
+    /*
+        NFT wagers escrow the token of the host instead of funds,
+        so the stake policy applies to the native value it accepts.
+    */
+    let stake = match &nft_wager {
+        Some(nft_wager) => {
+            if !info.funds.is_empty() {
+                return Err(ContractError::InvalidReceivedFunds {});
+            }
+            if let Some(native_value) = &nft_wager.native_value {
+                validate_stake(deps.storage, slice::from_ref(native_value))?;
+            }
+            vec![]
+        }
+        None => validate_stake(deps.storage, &info.funds)?,
+    };
 
-    let mut game_id: u64 = GAMES_COUNT.load(deps.storage)?;
-    game_id = game_id + 1;
-    GAMES_COUNT.save(deps.storage, &game_id)?;
-
-    let game = Game::new(coord, info.funds);
-    GAMES.save(
-        deps.storage,
-        (&info.sender, &opponent_address, game_id),
-        &game,
-    )?;
+    let game_id = start_game(deps.storage, &info.sender, &opponent_address)?;
+    let mut game = Game::new(
+        info.sender,
+        opponent_address,
+        ruleset,
+        first_player,
+        coord,
+        stake,
+    );
+    game.coin_flip = commitment.map(CoinFlip::new);
+    game.token = token;
+    game.nft_wager = nft_wager;
+    games().save(deps.storage, game_id, &game)?;
+    if let Some(coord) = coord {
+        record_move(
+            deps.storage,
+            &env,
+            game_id,
+            game.host_symbol,
+            game.host_symbol,
+            coord,
+        )?;
+    }
 
     Ok(Response::new()
         .add_attribute("method", "invite")
-        .add_attribute("x", coord.x.to_string())
-        .add_attribute("y", coord.y.to_string())
+        .add_attributes(coord_attributes(coord))
+        .add_attribute("first_player", first_player.to_string())
         .add_attribute("game_id", game_id.to_string())
         .add_attribute("opponent", opponent))
 }
@@ -46,20 +127,16 @@ pub fn try_reject(
     game_id: u64,
 ) -> Result<Response, ContractError> {
     let opponent_address = deps.api.addr_validate(&opponent)?;
-    let key: (&Addr, &Addr, u64);
-    let refund_address: &Addr;
-
-    if as_host {
-        key = (&info.sender, &opponent_address, game_id);
-        refund_address = &info.sender;
+    let (host_address, invited_address): (&Addr, &Addr) = if as_host {
+        (&info.sender, &opponent_address)
     } else {
-        key = (&opponent_address, &info.sender, game_id);
-        refund_address = &opponent_address;
+        (&opponent_address, &info.sender)
     };
+    let refund_address = host_address;
 
-    let game = GAMES
-        .may_load(deps.storage, key)
-        .unwrap()
+    let game = games()
+        .may_load(deps.storage, game_id)?
+        .filter(|game| game.is_between(host_address, invited_address))
         .filter(|game| game.status == Status::INVITED);
 
     if game.is_none() {
@@ -70,33 +147,38 @@ pub fn try_reject(
     } else {
         let mut game = game.unwrap();
         game.status = Status::REJECTED;
-        GAMES.save(deps.storage, key, &game)?;
+        games().save(deps.storage, game_id, &game)?;
+        close_game(deps.storage, &game.host, &game.opponent, game_id)?;
+        update_stats(deps.storage, &info.sender, |stats| stats.rejections += 1)?;
+        let messages = match &game.nft_wager {
+            Some(nft_wager) => nft_wager_messages(&game, nft_wager, None)?,
+            None => vec![prize_message(&game, refund_address, game.prize.clone())?],
+        };
 
         Ok(Response::new()
             .add_attribute("method", "reject")
             .add_attribute("opponent", opponent)
-            .add_message(BankMsg::Send {
-                to_address: refund_address.to_string(),
-                amount: game.prize.clone(),
-            }))
+            .add_messages(messages))
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn try_accept(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    coord: Coord,
+    coord: Option<Coord>,
     host: String,
     game_id: u64,
+    nonce: Option<Binary>,
+    token: Option<Addr>,
+    nft: Option<Nft>,
 ) -> Result<Response, ContractError> {
     let host_address = deps.api.addr_validate(&host)?;
-    if !coord.is_valid() {
-        return Err(ContractError::InvalidCoord { coord });
-    }
 
-    let game = GAMES
-        .may_load(deps.storage, (&host_address, &info.sender, game_id))
-        .unwrap()
+    let game = games()
+        .may_load(deps.storage, game_id)?
+        .filter(|game| game.is_between(&host_address, &info.sender))
         .filter(|game| game.status == Status::INVITED);
 
     if game.is_none() {
@@ -106,46 +188,74 @@ pub fn try_accept(
         });
     } else {
         let mut game = game.unwrap();
-        if game.already_played_on(coord) {
-            return Err(ContractError::CoordinateAlreadyPlayed { coord });
-        } else if game.prize.ne(&info.funds) {
-            return Err(ContractError::InvalidReceivedFunds {});
+        validate_accept_coord(&mut game, coord)?;
+        let funds = normalize_coins(&info.funds);
+        let stake = match &mut game.nft_wager {
+            Some(nft_wager) => {
+                if token.is_some() || !nft_wager.accepts(nft.as_ref(), &funds) {
+                    return Err(match nft {
+                        Some(_) => ContractError::InvalidNftWager {
+                            reason: String::from("the token must belong to an allowed collection"),
+                        },
+                        None => ContractError::InvalidReceivedFunds {},
+                    });
+                }
+                match nft {
+                    Some(nft) => nft_wager.opponent_nft = Some(nft),
+                    None => nft_wager.opponent_funds = funds,
+                }
+                vec![]
+            }
+            None if nft.is_some() || game.token != token || game.prize.ne(&funds) => {
+                return Err(ContractError::InvalidReceivedFunds {});
+            }
+            None => funds,
+        };
+        match (&game.coin_flip, &nonce) {
+            (Some(_), None) => return Err(ContractError::NonceRequired {}),
+            (None, Some(_)) => return Err(ContractError::CoinFlipNotAllowed {}),
+            _ => {}
         }
-        let game = game.double_prize().play(coord).finish_round();
-        game.status = Status::PLAYING;
 
-        GAMES.save(deps.storage, (&host_address, &info.sender, game_id), game)?;
+        game.status = Status::PLAYING;
+        if let Some(coin_flip) = &mut game.coin_flip {
+            let config = CONFIG.load(deps.storage)?;
+            coin_flip.nonce = nonce;
+            coin_flip.reveal_deadline = Some(env.block.time.plus_seconds(config.reveal_timeout));
+            game.status = Status::REVEALING;
+        }
+        begin_game(deps.storage, &env, game_id, &mut game, stake, coord)?;
     }
 
     Ok(Response::new()
         .add_attribute("method", "accept")
-        .add_attribute("x", coord.x.to_string())
-        .add_attribute("y", coord.y.to_string())
+        .add_attributes(coord_attributes(coord))
         .add_attribute("opponent", host_address))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn try_play(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     as_host: bool,
     coord: Coord,
     opponent: String,
     game_id: u64,
+    symbol: Option<PlayerSymbol>,
 ) -> Result<Response, ContractError> {
     let opponent_address = deps.api.addr_validate(&opponent)?;
-    if !coord.is_valid() {
-        return Err(ContractError::InvalidCoord { coord });
-    }
-    let key = if as_host {
-        (&info.sender, &opponent_address, game_id)
+    let (host_address, invited_address): (&Addr, &Addr) = if as_host {
+        (&info.sender, &opponent_address)
     } else {
-        (&opponent_address, &info.sender, game_id)
+        (&opponent_address, &info.sender)
     };
 
-    let game = GAMES
-        .may_load(deps.storage, key)
-        .unwrap()
-        .filter(|game| game.status == Status::PLAYING);
+    let game = games()
+        .may_load(deps.storage, game_id)?
+        .filter(|game| game.is_between(host_address, invited_address))
+        .filter(|game| game.status == Status::PLAYING)
+        .filter(|game| game.house_stake.is_none());
 
     if game.is_none() {
         return Err(ContractError::InvalidGame {
@@ -154,56 +264,86 @@ pub fn try_play(
         });
     } else {
         let mut game = game.unwrap();
-        if game.already_played_on(coord) {
+        if !game.ruleset.is_valid_coord(coord) {
+            return Err(ContractError::InvalidCoord { coord });
+        } else if game.already_played_on(coord) {
             return Err(ContractError::CoordinateAlreadyPlayed { coord });
+        } else if !game.is_playable(coord) {
+            return Err(ContractError::SubBoardNotPlayable { coord });
         } else if game.already_played(as_host) {
             return Err(ContractError::TurnAlreadyPlayed {
                 second_player: opponent,
             });
+        } else if game.ruleset.wild && symbol.is_none() {
+            return Err(ContractError::SymbolRequired {});
+        } else if !game.ruleset.wild && symbol.is_some() && symbol != Some(game.default_symbol()) {
+            return Err(ContractError::SymbolNotAllowed {});
         }
 
-        let game = game.play(coord);
-
-        if game.is_current_player_winner() {
-            game.status = Status::COMPLETED;
-            game.winner = Some(game.player_round.unwrap());
-            game.player_round = None;
-        } else if game.is_full_board() {
-            game.status = Status::COMPLETED;
-            game.player_round = None;
-        } else {
-            game.finish_round();
+        let player = game.player_round.unwrap();
+        let symbol = symbol.unwrap_or_else(|| game.default_symbol());
+        let game = game.play_symbol(coord, symbol).resolve_move(coord);
+        if game.turn_started.is_some() {
+            game.turn_started = Some(env.block.time);
         }
 
-        GAMES.save(deps.storage, key, game)?;
+        games().save(deps.storage, game_id, game)?;
+        record_move(deps.storage, &env, game_id, player, symbol, coord)?;
+        if game.status == Status::COMPLETED {
+            close_game(deps.storage, &game.host, &game.opponent, game_id)?;
+            record_completed_game(deps.storage, &env, game)?;
+        }
 
         let res = Response::new()
             .add_attribute("method", "play")
-            .add_attribute("x", coord.x.to_string())
-            .add_attribute("y", coord.y.to_string())
+            .add_attributes(coord_attributes(Some(coord)))
             .add_attribute("status", game.status.to_string())
             .add_attribute("opponent", opponent.clone());
 
         if game.status == Status::COMPLETED {
-            if game.winner.is_some() {
+            if let Some(series_id) = game.series {
+                let (series, messages) = advance_series(deps.storage, &env, series_id, game)?;
+                return Ok(res
+                    .add_attributes(game.winner.map(|winner| ("winner", winner.to_string())))
+                    .add_attribute("series_id", series_id.to_string())
+                    .add_attribute("series_status", series.status.to_string())
+                    .add_messages(messages));
+            } else if let Some(tournament_id) = game.tournament {
+                let (tournament, messages) =
+                    advance_tournament(deps.storage, &env, tournament_id, game_id, game)?;
+                return Ok(res
+                    .add_attributes(game.winner.map(|winner| ("winner", winner.to_string())))
+                    .add_attribute("tournament_id", tournament_id.to_string())
+                    .add_attribute("round", tournament.round.to_string())
+                    .add_attribute("tournament_status", tournament.status.to_string())
+                    .add_messages(messages));
+            } else if let Some(league_id) = game.league {
+                let (league, messages) = advance_league(deps.storage, league_id, game)?;
+                return Ok(res
+                    .add_attributes(game.winner.map(|winner| ("winner", winner.to_string())))
+                    .add_attribute("league_id", league_id.to_string())
+                    .add_attribute("league_status", league.status.to_string())
+                    .add_messages(messages));
+            } else if let Some(nft_wager) = &game.nft_wager {
+                let winner_address = game.winner.map(|winner| game.player_of(winner));
+                return Ok(res
+                    .add_attributes(game.winner.map(|winner| ("winner", winner.to_string())))
+                    .add_messages(nft_wager_messages(game, nft_wager, winner_address)?));
+            } else if let Some(winner) = game.winner {
+                let winner_address = game.player_of(winner);
+                let prize = take_fee(deps.storage, game.token.as_ref(), game.prize.clone())?;
+                update_stats(deps.storage, winner_address, |stats| stats.add_won(&prize))?;
                 return Ok(res
-                    .add_attribute("winner", game.winner.unwrap().to_string())
-                    .add_message(BankMsg::Send {
-                        to_address: info.sender.to_string(),
-                        amount: game.prize.clone(),
-                    }));
+                    .add_attribute("winner", winner.to_string())
+                    .add_message(prize_message(game, winner_address, prize)?));
             } else {
-                let prize = game.get_half_prize();
+                let (host_refund, opponent_refund) = game.draw_refunds();
+                let host_refund = take_fee(deps.storage, game.token.as_ref(), host_refund)?;
+                let opponent_refund = take_fee(deps.storage, game.token.as_ref(), opponent_refund)?;
 
                 return Ok(res.add_messages(vec![
-                    BankMsg::Send {
-                        to_address: info.sender.to_string(),
-                        amount: prize.clone(),
-                    },
-                    BankMsg::Send {
-                        to_address: opponent,
-                        amount: prize.clone(),
-                    },
+                    prize_message(game, &game.host, host_refund)?,
+                    prize_message(game, &game.opponent, opponent_refund)?,
                 ]));
             }
         }
@@ -211,3 +351,2266 @@ pub fn try_play(
         Ok(res)
     }
 }
+
+pub fn try_reveal(
+    deps: DepsMut,
+    info: MessageInfo,
+    game_id: u64,
+    secret: Binary,
+) -> Result<Response, ContractError> {
+    let mut game = revealing_game(deps.storage, game_id)?;
+    if info.sender != game.host {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let coin_flip = game.coin_flip.clone().unwrap();
+    if !coin_flip.is_revealed_by(&secret) {
+        return Err(ContractError::InvalidSecret {
+            min_length: MIN_SECRET_LENGTH,
+        });
+    }
+    let first_player = if coin_flip.host_moves_first(&secret) {
+        game.host_symbol = PlayerSymbol::X;
+        FirstPlayer::Host
+    } else {
+        game.host_symbol = PlayerSymbol::O;
+        FirstPlayer::Opponent
+    };
+    game.status = Status::PLAYING;
+    games().save(deps.storage, game_id, &game)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "reveal")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("first_player", first_player.to_string()))
+}
+
+/**
+    Lets the opponent of a coin flip game take the whole prize
+    once the host lets the reveal deadline pass, and anyone
+    award a series, tournament or league game to the player
+    waiting for a move once the move timeout passes.
+*/
+pub fn try_claim_timeout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    game_id: u64,
+) -> Result<Response, ContractError> {
+    if let Some(game) = games()
+        .may_load(deps.storage, game_id)?
+        .filter(|game| game.status == Status::PLAYING && game.turn_started.is_some())
+    {
+        return claim_move_timeout(deps.storage, &env, game_id, game);
+    }
+
+    let mut game = revealing_game(deps.storage, game_id)?;
+    if info.sender != game.opponent {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let deadline = game
+        .coin_flip
+        .as_ref()
+        .and_then(|coin_flip| coin_flip.reveal_deadline)
+        .unwrap_or_default();
+    if env.block.time <= deadline {
+        return Err(ContractError::RevealNotExpired { deadline });
+    }
+
+    game.status = Status::COMPLETED;
+    game.player_round = None;
+    game.winner = Some(game.host_symbol.opposite());
+    games().save(deps.storage, game_id, &game)?;
+    close_game(deps.storage, &game.host, &game.opponent, game_id)?;
+    record_completed_game(deps.storage, &env, &game)?;
+    let messages = match &game.nft_wager {
+        Some(nft_wager) => nft_wager_messages(&game, nft_wager, Some(&game.opponent))?,
+        None => {
+            let prize = take_fee(deps.storage, game.token.as_ref(), game.prize.clone())?;
+            update_stats(deps.storage, &game.opponent, |stats| stats.add_won(&prize))?;
+            vec![prize_message(&game, &game.opponent, prize)?]
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "claim_timeout")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("winner", game.opponent.to_string())
+        .add_messages(messages))
+}
+
+/**
+    Awards a series, tournament or league game to the player that is not
+    on turn, advancing it as if the game was won on the board.
+*/
+fn claim_move_timeout(
+    storage: &mut dyn Storage,
+    env: &Env,
+    game_id: u64,
+    mut game: Game,
+) -> Result<Response, ContractError> {
+    let move_timeout = match (game.series, game.tournament, game.league) {
+        (Some(series_id), _, _) => SERIES.load(storage, series_id)?.move_timeout,
+        (_, Some(tournament_id), _) => TOURNAMENTS.load(storage, tournament_id)?.move_timeout,
+        (_, _, Some(league_id)) => LEAGUES.load(storage, league_id)?.move_timeout,
+        _ => return Err(StdError::not_found(format!("competition of game {}", game_id)).into()),
+    };
+    let deadline = game
+        .turn_started
+        .unwrap_or_default()
+        .plus_seconds(move_timeout);
+    if env.block.time <= deadline {
+        return Err(ContractError::MoveNotExpired { deadline });
+    }
+
+    let winner = game.player_round.unwrap().opposite();
+    game.status = Status::COMPLETED;
+    game.player_round = None;
+    game.winner = Some(winner);
+    games().save(storage, game_id, &game)?;
+    close_game(storage, &game.host, &game.opponent, game_id)?;
+    record_completed_game(storage, env, &game)?;
+
+    let res = Response::new()
+        .add_attribute("method", "claim_timeout")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("winner", game.player_of(winner).to_string());
+    if let Some(series_id) = game.series {
+        let (series, messages) = advance_series(storage, env, series_id, &game)?;
+        Ok(res
+            .add_attribute("series_id", series_id.to_string())
+            .add_attribute("series_status", series.status.to_string())
+            .add_messages(messages))
+    } else if let Some(tournament_id) = game.tournament {
+        let (tournament, messages) =
+            advance_tournament(storage, env, tournament_id, game_id, &game)?;
+        Ok(res
+            .add_attribute("tournament_id", tournament_id.to_string())
+            .add_attribute("round", tournament.round.to_string())
+            .add_attribute("tournament_status", tournament.status.to_string())
+            .add_messages(messages))
+    } else {
+        let league_id = game.league.unwrap();
+        let (league, messages) = advance_league(storage, league_id, &game)?;
+        Ok(res
+            .add_attribute("league_id", league_id.to_string())
+            .add_attribute("league_status", league.status.to_string())
+            .add_messages(messages))
+    }
+}
+
+/**
+    Invites the other player of a completed game to a new one
+    with the same ruleset and stake, where the player that moved
+    second on the previous game moves first.
+*/
+pub fn try_rematch(
+    deps: DepsMut,
+    info: MessageInfo,
+    game_id: u64,
+    token: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let previous_game = games()
+        .may_load(deps.storage, game_id)?
+        .filter(|game| game.status == Status::COMPLETED)
+        .filter(|game| game.house_stake.is_none())
+        .ok_or_else(|| StdError::not_found(format!("completed game {}", game_id)))?;
+    let opponent = if info.sender == previous_game.host {
+        previous_game.opponent.clone()
+    } else if info.sender == previous_game.opponent {
+        previous_game.host.clone()
+    } else {
+        return Err(ContractError::Unauthorized {});
+    };
+    if REMATCHES.has(deps.storage, game_id) {
+        return Err(ContractError::RematchAlreadyStarted { game_id });
+    }
+    let stake = validate_stake(deps.storage, &info.funds)?;
+    if previous_game.nft_wager.is_some()
+        || previous_game.token != token
+        || previous_game.stake_of(&info.sender).ne(&stake)
+    {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    let first_player = if *previous_game.player_of(PlayerSymbol::X) == info.sender {
+        FirstPlayer::Opponent
+    } else {
+        FirstPlayer::Host
+    };
+    let rematch_id = start_game(deps.storage, &info.sender, &opponent)?;
+    let mut game = Game::new(
+        info.sender,
+        opponent.clone(),
+        previous_game.ruleset,
+        first_player,
+        None,
+        stake,
+    );
+    game.previous_game = Some(game_id);
+    game.token = token;
+    games().save(deps.storage, rematch_id, &game)?;
+    REMATCHES.save(deps.storage, game_id, &rematch_id)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "rematch")
+        .add_attribute("previous_game_id", game_id.to_string())
+        .add_attribute("first_player", first_player.to_string())
+        .add_attribute("game_id", rematch_id.to_string())
+        .add_attribute("opponent", opponent))
+}
+
+/**
+    Handles CW20 stakes: the token contract calls it when a player
+    sends tokens along with an embedded message, which is handled
+    as if the player sent the tokens as funds.
+*/
+pub fn try_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let token = info.sender;
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&wrapper.sender)?,
+        funds: vec![Coin {
+            denom: token.to_string(),
+            amount: wrapper.amount,
+        }],
+    };
+
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Invite {
+            coord,
+            opponent,
+            ruleset,
+            first_player,
+            commitment,
+        } => try_invite(
+            deps,
+            env,
+            info,
+            coord,
+            opponent,
+            ruleset,
+            first_player,
+            commitment,
+            Some(token),
+            None,
+        ),
+        ReceiveMsg::Accept {
+            coord,
+            host,
+            game_id,
+            nonce,
+        } => try_accept(
+            deps,
+            env,
+            info,
+            coord,
+            host,
+            game_id,
+            nonce,
+            Some(token),
+            None,
+        ),
+        ReceiveMsg::Rematch { game_id } => try_rematch(deps, info, game_id, Some(token)),
+    }
+}
+
+/**
+    Handles NFT wagers: the CW721 contract calls it when a player
+    sends a token along with an embedded message, which escrows
+    the token as the stake of the player on the game.
+*/
+pub fn try_receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let nft = Nft {
+        collection: info.sender,
+        token_id: wrapper.token_id,
+    };
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&wrapper.sender)?,
+        funds: vec![],
+    };
+
+    match from_binary(&wrapper.msg)? {
+        ReceiveNftMsg::Invite {
+            coord,
+            opponent,
+            ruleset,
+            first_player,
+            commitment,
+            allowed_collections,
+            native_value,
+        } => {
+            if allowed_collections.is_empty() && native_value.is_none() {
+                return Err(ContractError::InvalidNftWager {
+                    reason: String::from("the opponent must be allowed to escrow a stake"),
+                });
+            } else if matches!(&native_value, Some(coin) if coin.amount.is_zero()) {
+                return Err(ContractError::InvalidNftWager {
+                    reason: String::from("the native value must not be zero"),
+                });
+            }
+            let allowed_collections = allowed_collections
+                .iter()
+                .map(|collection| deps.api.addr_validate(collection))
+                .collect::<StdResult<Vec<Addr>>>()?;
+
+            try_invite(
+                deps,
+                env,
+                info,
+                coord,
+                opponent,
+                ruleset,
+                first_player,
+                commitment,
+                None,
+                Some(NftWager {
+                    host_nft: nft,
+                    allowed_collections,
+                    native_value,
+                    opponent_nft: None,
+                    opponent_funds: vec![],
+                }),
+            )
+        }
+        ReceiveNftMsg::Accept {
+            coord,
+            host,
+            game_id,
+            nonce,
+        } => try_accept(
+            deps,
+            env,
+            info,
+            coord,
+            host,
+            game_id,
+            nonce,
+            None,
+            Some(nft),
+        ),
+    }
+}
+
+pub fn try_create_series(
+    deps: DepsMut,
+    info: MessageInfo,
+    opponent: String,
+    best_of: u8,
+    ruleset: Option<Ruleset>,
+    move_timeout: Option<u64>,
+) -> Result<Response, ContractError> {
+    let opponent_address = deps.api.addr_validate(&opponent)?;
+    let ruleset = ruleset.unwrap_or_default();
+    let move_timeout = move_timeout.unwrap_or(DEFAULT_MOVE_TIMEOUT);
+    if !ruleset.is_valid() {
+        return Err(ContractError::InvalidRuleset {
+            min: MIN_BOARD_SIZE,
+            max: ruleset.max_board_size(),
+        });
+    } else if !Series::is_valid_best_of(best_of) {
+        return Err(ContractError::InvalidSeries {
+            max: MAX_SERIES_GAMES,
+        });
+    } else if move_timeout == 0 {
+        return Err(ContractError::InvalidMoveTimeout {});
+    } else if opponent_address == info.sender {
+        return Err(ContractError::CannotStartGame {});
+    }
+    let stake = validate_stake(deps.storage, &info.funds)?;
+    if stake.is_empty() {
+        return Err(ContractError::StakeRequired {});
+    }
+
+    let series_id = SERIES_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SERIES_COUNT.save(deps.storage, &series_id)?;
+    SERIES.save(
+        deps.storage,
+        series_id,
+        &Series {
+            host: info.sender,
+            opponent: opponent_address,
+            best_of,
+            ruleset,
+            move_timeout,
+            prize: stake,
+            host_wins: 0,
+            opponent_wins: 0,
+            draws: 0,
+            game_ids: vec![],
+            status: Status::INVITED,
+            winner: None,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_series")
+        .add_attribute("series_id", series_id.to_string())
+        .add_attribute("best_of", best_of.to_string())
+        .add_attribute("opponent", opponent))
+}
+
+/**
+    Matches the stake of the host and starts
+    the first game of the series.
+*/
+pub fn try_accept_series(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    series_id: u64,
+) -> Result<Response, ContractError> {
+    let mut series = invited_series(deps.storage, series_id)?;
+    if info.sender != series.opponent {
+        return Err(ContractError::Unauthorized {});
+    } else if series.prize.ne(&normalize_coins(&info.funds)) {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    for player in [&series.host, &series.opponent] {
+        update_stats(deps.storage, player, |stats| {
+            stats.add_staked(&series.prize)
+        })?;
+    }
+    series.double_prize().status = Status::PLAYING;
+    let game_id = start_series_game(deps.storage, &env, series_id, &mut series)?;
+    SERIES.save(deps.storage, series_id, &series)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_series")
+        .add_attribute("series_id", series_id.to_string())
+        .add_attribute("game_id", game_id.to_string()))
+}
+
+/**
+    Lets either player cancel a series that has not been
+    accepted yet, refunding the stake of the host.
+*/
+pub fn try_reject_series(
+    deps: DepsMut,
+    info: MessageInfo,
+    series_id: u64,
+) -> Result<Response, ContractError> {
+    let mut series = invited_series(deps.storage, series_id)?;
+    if info.sender != series.host && info.sender != series.opponent {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    series.status = Status::REJECTED;
+    SERIES.save(deps.storage, series_id, &series)?;
+    update_stats(deps.storage, &info.sender, |stats| stats.rejections += 1)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "reject_series")
+        .add_attribute("series_id", series_id.to_string())
+        .add_message(BankMsg::Send {
+            to_address: series.host.to_string(),
+            amount: series.prize,
+        }))
+}
+
+pub fn try_open_invite(
+    deps: DepsMut,
+    info: MessageInfo,
+    ruleset: Option<Ruleset>,
+    first_player: Option<FirstPlayer>,
+    allow_list: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let ruleset = ruleset.unwrap_or_default();
+    let first_player = first_player.unwrap_or_default();
+    if !ruleset.is_valid() {
+        return Err(ContractError::InvalidRuleset {
+            min: MIN_BOARD_SIZE,
+            max: ruleset.max_board_size(),
+        });
+    } else if first_player == FirstPlayer::Random {
+        return Err(ContractError::CoinFlipRequired {});
+    }
+    let stake = validate_stake(deps.storage, &info.funds)?;
+    if stake.len() != 1 {
+        return Err(ContractError::InvalidStake {});
+    }
+    let allow_list = allow_list
+        .map(|allow_list| {
+            allow_list
+                .iter()
+                .map(|player| deps.api.addr_validate(player))
+                .collect::<StdResult<Vec<Addr>>>()
+        })
+        .transpose()?;
+
+    let invite_id = post_open_invite(
+        deps.storage,
+        &OpenInvite {
+            host: info.sender,
+            stake: stake[0].clone(),
+            ruleset,
+            first_player,
+            allow_list,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "open_invite")
+        .add_attribute("invite_id", invite_id.to_string()))
+}
+
+pub fn try_accept_open_invite(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    invite_id: u64,
+    coord: Option<Coord>,
+) -> Result<Response, ContractError> {
+    let invite = open_invites()
+        .may_load(deps.storage, invite_id)?
+        .ok_or_else(|| StdError::not_found(format!("open invite {}", invite_id)))?;
+    if !invite.allows(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    } else if normalize_coins(&info.funds) != vec![invite.stake.clone()] {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    let game_id = join_open_invite(deps.storage, &env, invite_id, invite, info.sender, coord)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_open_invite")
+        .add_attributes(coord_attributes(coord))
+        .add_attribute("invite_id", invite_id.to_string())
+        .add_attribute("game_id", game_id.to_string()))
+}
+
+pub fn try_cancel_open_invite(
+    deps: DepsMut,
+    info: MessageInfo,
+    invite_id: u64,
+) -> Result<Response, ContractError> {
+    let invite = open_invites()
+        .may_load(deps.storage, invite_id)?
+        .ok_or_else(|| StdError::not_found(format!("open invite {}", invite_id)))?;
+    if info.sender != invite.host {
+        return Err(ContractError::Unauthorized {});
+    }
+    open_invites().remove(deps.storage, invite_id)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "cancel_open_invite")
+        .add_attribute("invite_id", invite_id.to_string())
+        .add_message(BankMsg::Send {
+            to_address: invite.host.to_string(),
+            amount: vec![invite.stake],
+        }))
+}
+
+/**
+    Accepts the oldest open invite with the same stake and the
+    default ruleset that the caller can join, or posts a new
+    open invite with a random first player when there is none.
+*/
+pub fn try_auto_match(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stake: Coin,
+) -> Result<Response, ContractError> {
+    if validate_stake(deps.storage, &info.funds)? != vec![stake.clone()] {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    /*
+        Only public invites of the default ruleset and the exact same stake
+        are looked at, oldest first. The scan stops after MAX_AUTO_MATCH_SCAN
+        candidates so that invites the sender cannot take cannot push the
+        match over the gas limit, and a new invite is posted instead.
+    */
+    let mut compatible_invite = None;
+    for record in open_invites()
+        .idx
+        .auto_match
+        .prefix((stake.denom.clone(), stake.amount.u128(), 1))
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(MAX_AUTO_MATCH_SCAN)
+    {
+        let (invite_id, invite) = record?;
+        if invite.allows(&info.sender) && can_start_game(deps.storage, &invite.host, &info.sender)?
+        {
+            compatible_invite = Some((invite_id, invite));
+            break;
+        }
+    }
+
+    let res = Response::new().add_attribute("method", "auto_match");
+    match compatible_invite {
+        Some((invite_id, invite)) => {
+            let game_id =
+                join_open_invite(deps.storage, &env, invite_id, invite, info.sender, None)?;
+            Ok(res
+                .add_attribute("invite_id", invite_id.to_string())
+                .add_attribute("game_id", game_id.to_string()))
+        }
+        None => {
+            let invite_id = post_open_invite(
+                deps.storage,
+                &OpenInvite {
+                    host: info.sender,
+                    stake,
+                    ruleset: Ruleset::default(),
+                    first_player: FirstPlayer::Host,
+                    allow_list: None,
+                },
+            )?;
+            Ok(res.add_attribute("invite_id", invite_id.to_string()))
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_create_tournament(
+    deps: DepsMut,
+    info: MessageInfo,
+    entry_fee: Coin,
+    max_players: u32,
+    start_time: Timestamp,
+    payout_split: Vec<u8>,
+    ruleset: Option<Ruleset>,
+    move_timeout: Option<u64>,
+) -> Result<Response, ContractError> {
+    let ruleset = ruleset.unwrap_or_default();
+    let move_timeout = move_timeout.unwrap_or(DEFAULT_MOVE_TIMEOUT);
+    if !ruleset.is_valid() {
+        return Err(ContractError::InvalidRuleset {
+            min: MIN_BOARD_SIZE,
+            max: ruleset.max_board_size(),
+        });
+    } else if let Some(reason) = entry_fee_error(&entry_fee) {
+        return Err(ContractError::InvalidTournament { reason });
+    } else if move_timeout == 0 {
+        return Err(ContractError::InvalidTournament {
+            reason: String::from("move_timeout must be at least 1 second"),
+        });
+    } else if !(2..=MAX_TOURNAMENT_PLAYERS).contains(&max_players) {
+        return Err(ContractError::InvalidTournament {
+            reason: format!(
+                "max_players must be between 2 and {}",
+                MAX_TOURNAMENT_PLAYERS
+            ),
+        });
+    } else if let Some(reason) = payout_split_error(&payout_split, max_players) {
+        return Err(ContractError::InvalidTournament { reason });
+    }
+    validate_stake(deps.storage, slice::from_ref(&entry_fee))?;
+
+    let tournament_id = TOURNAMENTS_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    TOURNAMENTS_COUNT.save(deps.storage, &tournament_id)?;
+    TOURNAMENTS.save(
+        deps.storage,
+        tournament_id,
+        &Tournament {
+            organizer: info.sender,
+            entry_fee,
+            max_players,
+            start_time,
+            payout_split,
+            ruleset,
+            move_timeout,
+            players: vec![],
+            round: 0,
+            byes: vec![],
+            matches: vec![],
+            eliminated: vec![],
+            status: Status::INVITED,
+            winner: None,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string()))
+}
+
+/**
+    Registers the sender paying the entry fee, starting
+    the tournament once it reaches max_players.
+*/
+pub fn try_join_tournament(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    let mut tournament = open_tournament(deps.storage, tournament_id)?;
+    if tournament.players.contains(&info.sender) {
+        return Err(ContractError::AlreadyJoined {});
+    } else if validate_stake(deps.storage, &info.funds)? != vec![tournament.entry_fee.clone()] {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    tournament.players.push(info.sender);
+    if tournament.players.len() == tournament.max_players as usize {
+        start_tournament(deps.storage, &env, tournament_id, &mut tournament)?;
+    }
+    TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "join_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string())
+        .add_attribute("players", tournament.players.len().to_string())
+        .add_attribute("tournament_status", tournament.status.to_string()))
+}
+
+/**
+    Starts a tournament that is not full once its start time
+    is reached, or cancels it refunding the entry fees when
+    less than two players joined.
+*/
+pub fn try_start_tournament(
+    deps: DepsMut,
+    env: Env,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    let mut tournament = open_tournament(deps.storage, tournament_id)?;
+    if env.block.time < tournament.start_time {
+        return Err(ContractError::TournamentNotStarted {
+            start_time: tournament.start_time,
+        });
+    }
+
+    let mut res = Response::new()
+        .add_attribute("method", "start_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string());
+    if tournament.players.len() < 2 {
+        tournament.status = Status::REJECTED;
+        for player in &tournament.players {
+            res = res.add_message(BankMsg::Send {
+                to_address: player.to_string(),
+                amount: vec![tournament.entry_fee.clone()],
+            });
+        }
+    } else {
+        start_tournament(deps.storage, &env, tournament_id, &mut tournament)?;
+    }
+    TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+    Ok(res.add_attribute("tournament_status", tournament.status.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_create_league(
+    deps: DepsMut,
+    info: MessageInfo,
+    entry_fee: Coin,
+    max_players: u32,
+    start_time: Timestamp,
+    double_round_robin: bool,
+    points: Option<LeaguePoints>,
+    payout_split: Vec<u8>,
+    ruleset: Option<Ruleset>,
+    move_timeout: Option<u64>,
+) -> Result<Response, ContractError> {
+    let ruleset = ruleset.unwrap_or_default();
+    let move_timeout = move_timeout.unwrap_or(DEFAULT_MOVE_TIMEOUT);
+    if !ruleset.is_valid() {
+        return Err(ContractError::InvalidRuleset {
+            min: MIN_BOARD_SIZE,
+            max: ruleset.max_board_size(),
+        });
+    } else if let Some(reason) = entry_fee_error(&entry_fee) {
+        return Err(ContractError::InvalidLeague { reason });
+    } else if move_timeout == 0 {
+        return Err(ContractError::InvalidLeague {
+            reason: String::from("move_timeout must be at least 1 second"),
+        });
+    } else if !(2..=MAX_LEAGUE_PLAYERS).contains(&max_players) {
+        return Err(ContractError::InvalidLeague {
+            reason: format!("max_players must be between 2 and {}", MAX_LEAGUE_PLAYERS),
+        });
+    } else if let Some(reason) = payout_split_error(&payout_split, max_players) {
+        return Err(ContractError::InvalidLeague { reason });
+    }
+    validate_stake(deps.storage, slice::from_ref(&entry_fee))?;
+
+    let league_id = LEAGUES_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LEAGUES_COUNT.save(deps.storage, &league_id)?;
+    LEAGUES.save(
+        deps.storage,
+        league_id,
+        &League {
+            organizer: info.sender,
+            entry_fee,
+            max_players,
+            start_time,
+            double_round_robin,
+            points: points.unwrap_or_default(),
+            payout_split,
+            ruleset,
+            move_timeout,
+            standings: vec![],
+            games_left: 0,
+            status: Status::INVITED,
+            winner: None,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_league")
+        .add_attribute("league_id", league_id.to_string()))
+}
+
+/**
+    Registers the sender paying the entry fee, scheduling
+    the games of the league once it reaches max_players.
+*/
+pub fn try_join_league(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    league_id: u64,
+) -> Result<Response, ContractError> {
+    let mut league = open_league(deps.storage, league_id)?;
+    if league.players().contains(&info.sender) {
+        return Err(ContractError::AlreadyJoined {});
+    } else if validate_stake(deps.storage, &info.funds)? != vec![league.entry_fee.clone()] {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    league.standings.push(LeagueStanding::new(info.sender));
+    if league.standings.len() == league.max_players as usize {
+        schedule_league(deps.storage, &env, league_id, &mut league)?;
+    }
+    LEAGUES.save(deps.storage, league_id, &league)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "join_league")
+        .add_attribute("league_id", league_id.to_string())
+        .add_attribute("players", league.standings.len().to_string())
+        .add_attribute("league_status", league.status.to_string()))
+}
+
+/**
+    Schedules the games of a league that is not full once its
+    start time is reached, or cancels it refunding the entry
+    fees when less than two players joined.
+*/
+pub fn try_start_league(
+    deps: DepsMut,
+    env: Env,
+    league_id: u64,
+) -> Result<Response, ContractError> {
+    let mut league = open_league(deps.storage, league_id)?;
+    if env.block.time < league.start_time {
+        return Err(ContractError::LeagueNotStarted {
+            start_time: league.start_time,
+        });
+    }
+
+    let mut res = Response::new()
+        .add_attribute("method", "start_league")
+        .add_attribute("league_id", league_id.to_string());
+    if league.standings.len() < 2 {
+        league.status = Status::REJECTED;
+        for player in league.players() {
+            res = res.add_message(BankMsg::Send {
+                to_address: player.to_string(),
+                amount: vec![league.entry_fee.clone()],
+            });
+        }
+    } else {
+        schedule_league(deps.storage, &env, league_id, &mut league)?;
+    }
+    LEAGUES.save(deps.storage, league_id, &league)?;
+
+    Ok(res.add_attribute("league_status", league.status.to_string()))
+}
+
+/**
+    Lets the admin schedule a season after the last one,
+    funding its prize pool with the coin sent.
+*/
+pub fn try_create_season(
+    deps: DepsMut,
+    info: MessageInfo,
+    start_time: Timestamp,
+    end_time: Timestamp,
+    payout_split: Vec<u8>,
+    points: Option<LeaguePoints>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let last_season = SEASONS
+        .range(deps.storage, None, None, Order::Descending)
+        .next()
+        .transpose()?;
+    if end_time <= start_time {
+        return Err(ContractError::InvalidSeason {
+            reason: String::from("end_time must be after start_time"),
+        });
+    } else if let Some((_, last_season)) =
+        last_season.filter(|(_, last_season)| start_time < last_season.end_time)
+    {
+        return Err(ContractError::InvalidSeason {
+            reason: format!(
+                "start_time must not be before the end of the last season at {}",
+                last_season.end_time
+            ),
+        });
+    } else if info.funds.len() != 1 || info.funds[0].amount.is_zero() {
+        return Err(ContractError::InvalidSeason {
+            reason: String::from("the prize pool must be a single coin"),
+        });
+    } else if let Some(reason) = payout_split_error(&payout_split, MAX_SEASON_WINNERS) {
+        return Err(ContractError::InvalidSeason { reason });
+    }
+
+    let season_id = SEASONS_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SEASONS_COUNT.save(deps.storage, &season_id)?;
+    SEASONS.save(
+        deps.storage,
+        season_id,
+        &Season {
+            start_time,
+            end_time,
+            prize_pool: info.funds[0].clone(),
+            payout_split,
+            points: points.unwrap_or_default(),
+            status: Status::PLAYING,
+            winners: vec![],
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_season")
+        .add_attribute("season_id", season_id.to_string()))
+}
+
+/**
+    Pays the prize pool of an ended season to its top scoring
+    players, returning the shares nobody scored for and the
+    rounding remainder to the admin.
+*/
+pub fn try_finalize_season(
+    deps: DepsMut,
+    env: Env,
+    season_id: u64,
+) -> Result<Response, ContractError> {
+    let mut season = SEASONS
+        .may_load(deps.storage, season_id)?
+        .filter(|season| season.status == Status::PLAYING)
+        .ok_or_else(|| StdError::not_found(format!("active season {}", season_id)))?;
+    if env.block.time < season.end_time {
+        return Err(ContractError::SeasonNotEnded {
+            end_time: season.end_time,
+        });
+    }
+
+    season.winners = season_scores()
+        .idx
+        .points
+        .sub_prefix(season_id)
+        .range(deps.storage, None, None, Order::Descending)
+        .take_while(|record| !matches!(record, Ok((_, score)) if score.standing.points == 0))
+        .take(season.payout_split.len())
+        .map(|record| record.map(|(_, score)| score.standing.player))
+        .collect::<StdResult<_>>()?;
+    season.status = Status::COMPLETED;
+    SEASONS.save(deps.storage, season_id, &season)?;
+
+    let pool = &season.prize_pool;
+    let payouts = prize_shares(pool, season.winners.iter().collect(), &season.payout_split);
+    let paid: Uint128 = payouts.iter().map(|(_, amount)| *amount).sum();
+    let mut messages = payout_messages(&pool.denom, payouts);
+    record_winnings(deps.storage, &messages)?;
+    if paid < pool.amount {
+        messages.push(BankMsg::Send {
+            to_address: CONFIG.load(deps.storage)?.admin.to_string(),
+            amount: vec![Coin {
+                denom: pool.denom.clone(),
+                amount: pool.amount - paid,
+            }],
+        });
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "finalize_season")
+        .add_attribute("season_id", season_id.to_string())
+        .add_messages(messages))
+}
+
+/**
+    Plays a move against the house, which replies on the same
+    transaction. Sending a stake starts a new classic game where
+    the player moves first, otherwise the move is played on the
+    ongoing game of the player against the house.
+*/
+pub fn try_play_house(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    coord: Coord,
+    stake: Option<Coin>,
+) -> Result<Response, ContractError> {
+    let house = env.contract.address.clone();
+    let (game_id, mut game) = match stake {
+        Some(stake) => start_house_game(deps.storage, &info, &house, stake)?,
+        None if !info.funds.is_empty() => return Err(ContractError::InvalidReceivedFunds {}),
+        None => house_game(deps.storage, &info.sender, &house)?,
+    };
+    if game.status != Status::PLAYING {
+        return Err(ContractError::InvalidGame {
+            host: info.sender,
+            opponent: house,
+        });
+    } else if game.player_round != Some(game.host_symbol) {
+        return Err(ContractError::TurnAlreadyPlayed {
+            second_player: house.to_string(),
+        });
+    } else if !game.ruleset.is_valid_coord(coord) {
+        return Err(ContractError::InvalidCoord { coord });
+    } else if game.already_played_on(coord) {
+        return Err(ContractError::CoordinateAlreadyPlayed { coord });
+    }
+
+    let mut res = Response::new()
+        .add_attribute("method", "play_house")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attributes(coord_attributes(Some(coord)));
+    play_house_move(deps.storage, &env, game_id, &mut game, coord)?;
+    if game.status == Status::PLAYING {
+        let config = CONFIG.load(deps.storage)?;
+        let house_coord = match config.house.difficulty {
+            HouseDifficulty::Optimal => house::best_move(&game.board, game.default_symbol()),
+            HouseDifficulty::Random => house::random_move(
+                &game.board,
+                env.block.height ^ env.block.time.nanos() ^ game_id,
+            ),
+        }
+        .ok_or_else(|| StdError::generic_err("No move left for the house"))?;
+        play_house_move(deps.storage, &env, game_id, &mut game, house_coord)?;
+        res = res
+            .add_attribute("house_x", house_coord.x.to_string())
+            .add_attribute("house_y", house_coord.y.to_string());
+    }
+    games().save(deps.storage, game_id, &game)?;
+
+    if game.status == Status::COMPLETED {
+        close_game(deps.storage, &game.host, &game.opponent, game_id)?;
+        record_completed_game(deps.storage, &env, &game)?;
+        let messages = settle_house_game(deps.storage, &game)?;
+        res = res
+            .add_attributes(game.winner.map(|winner| ("winner", winner.to_string())))
+            .add_messages(messages);
+    }
+
+    Ok(res.add_attribute("status", game.status.to_string()))
+}
+
+/**
+    Adds the coins sent by the admin to the house bankroll.
+*/
+pub fn try_fund_house(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    } else if info.funds.is_empty() {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    for coin in &info.funds {
+        HOUSE_BANKROLL.update(deps.storage, &coin.denom, |bankroll| -> StdResult<_> {
+            Ok(bankroll.unwrap_or_default() + coin.amount)
+        })?;
+    }
+
+    Ok(Response::new().add_attribute("method", "fund_house"))
+}
+
+/**
+    Sends part of the house bankroll not reserved
+    by ongoing games back to the admin.
+*/
+pub fn try_withdraw_house(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Coin,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let available = HOUSE_BANKROLL
+        .may_load(deps.storage, &amount.denom)?
+        .unwrap_or_default();
+    if amount.amount > available {
+        return Err(ContractError::InsufficientBankroll { available });
+    }
+    HOUSE_BANKROLL.save(deps.storage, &amount.denom, &(available - amount.amount))?;
+
+    Ok(Response::new()
+        .add_attribute("method", "withdraw_house")
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![amount],
+        }))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_open_games_per_pair: Option<u32>,
+    reveal_timeout: Option<u64>,
+    house: Option<HouseConfig>,
+    treasury: Option<String>,
+    fee_bps: Option<u32>,
+    stake_limits: Option<Vec<StakeLimit>>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(max_open_games_per_pair) = max_open_games_per_pair {
+        if max_open_games_per_pair == 0 {
+            return Err(ContractError::InvalidConfig {
+                reason: String::from("max_open_games_per_pair must be at least 1"),
+            });
+        }
+        config.max_open_games_per_pair = max_open_games_per_pair;
+    }
+    if let Some(reveal_timeout) = reveal_timeout {
+        if reveal_timeout == 0 {
+            return Err(ContractError::InvalidConfig {
+                reason: String::from("reveal_timeout must be at least 1 second"),
+            });
+        }
+        config.reveal_timeout = reveal_timeout;
+    }
+    if let Some(house) = house {
+        if house.max_exposure_bps > MAX_BPS {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("house max_exposure_bps must be at most {}", MAX_BPS),
+            });
+        }
+        config.house = house;
+    }
+    if let Some(treasury) = treasury {
+        config.treasury = Some(deps.api.addr_validate(&treasury)?);
+    }
+    if let Some(fee_bps) = fee_bps {
+        if fee_bps > MAX_BPS {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("fee_bps must be at most {}", MAX_BPS),
+            });
+        }
+        config.fee_bps = fee_bps;
+    }
+    if let Some(stake_limits) = stake_limits {
+        for (index, limit) in stake_limits.iter().enumerate() {
+            if stake_limits[..index]
+                .iter()
+                .any(|other| other.denom == limit.denom)
+            {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("stake limits of {} are set more than once", limit.denom),
+                });
+            } else if limit.max.filter(|max| *max < limit.min).is_some() {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("stake limits of {} must have a max above min", limit.denom),
+                });
+            }
+        }
+        config.stake_limits = stake_limits;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_config")
+        .add_attribute(
+            "max_open_games_per_pair",
+            config.max_open_games_per_pair.to_string(),
+        )
+        .add_attribute("reveal_timeout", config.reveal_timeout.to_string())
+        .add_attribute("fee_bps", config.fee_bps.to_string()))
+}
+
+/**
+    Sends every fee collected so far to the treasury,
+    on behalf of the admin or the treasury itself.
+*/
+pub fn try_withdraw_fees(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let treasury = config.treasury();
+    if info.sender != config.admin && info.sender != *treasury {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let native = FEES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|record| record.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<Vec<Coin>>>()?;
+    let tokens = TOKEN_FEES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, Uint128)>>>()?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for coin in &native {
+        FEES.remove(deps.storage, &coin.denom);
+    }
+    if !native.is_empty() {
+        messages.push(
+            BankMsg::Send {
+                to_address: treasury.to_string(),
+                amount: native,
+            }
+            .into(),
+        );
+    }
+    for (token, amount) in tokens {
+        TOKEN_FEES.remove(deps.storage, &token);
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: token.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: treasury.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "withdraw_fees")
+        .add_attribute("treasury", treasury.to_string())
+        .add_messages(messages))
+}
+
+/**
+    Starts a game of the player against the house, reserving
+    the winnings the player could get from the bankroll.
+*/
+fn start_house_game(
+    storage: &mut dyn Storage,
+    info: &MessageInfo,
+    house: &Addr,
+    stake: Coin,
+) -> Result<(u64, Game), ContractError> {
+    if stake.amount.is_zero() || validate_stake(storage, &info.funds)? != vec![stake.clone()] {
+        return Err(ContractError::InvalidReceivedFunds {});
+    }
+
+    let config = CONFIG.load(storage)?;
+    let bankroll = HOUSE_BANKROLL
+        .may_load(storage, &stake.denom)?
+        .unwrap_or_default();
+    let max_exposure = bankroll.multiply_ratio(config.house.max_exposure_bps, MAX_BPS);
+    let winnings = stake
+        .amount
+        .multiply_ratio(config.house.payout_bps, MAX_BPS);
+    if winnings > max_exposure {
+        return Err(ContractError::HouseStakeTooHigh {
+            max: max_exposure.multiply_ratio(MAX_BPS, config.house.payout_bps.max(1)),
+        });
+    }
+    HOUSE_BANKROLL.save(storage, &stake.denom, &(bankroll - winnings))?;
+    update_stats(storage, &info.sender, |stats| {
+        stats.add_staked(slice::from_ref(&stake))
+    })?;
+
+    let game_id = start_game(storage, &info.sender, house)?;
+    let mut game = Game::new(
+        info.sender.clone(),
+        house.clone(),
+        Ruleset::default(),
+        FirstPlayer::Host,
+        None,
+        vec![Coin {
+            denom: stake.denom.clone(),
+            amount: stake.amount + winnings,
+        }],
+    );
+    game.status = Status::PLAYING;
+    game.host_stake = vec![stake.clone()];
+    game.opponent_stake = vec![Coin {
+        denom: stake.denom.clone(),
+        amount: winnings,
+    }];
+    game.house_stake = Some(stake);
+
+    Ok((game_id, game))
+}
+
+/**
+    Latest ongoing game of the player against the house.
+*/
+fn house_game(
+    storage: &dyn Storage,
+    player: &Addr,
+    house: &Addr,
+) -> Result<(u64, Game), ContractError> {
+    let game_ids = OPEN_GAMES
+        .may_load(storage, pair_key(player, house))?
+        .unwrap_or_default();
+    match game_ids.last() {
+        Some(game_id) => Ok((*game_id, games().load(storage, *game_id)?)),
+        None => Err(ContractError::InvalidGame {
+            host: player.clone(),
+            opponent: house.clone(),
+        }),
+    }
+}
+
+fn play_house_move(
+    storage: &mut dyn Storage,
+    env: &Env,
+    game_id: u64,
+    game: &mut Game,
+    coord: Coord,
+) -> StdResult<()> {
+    let player = game.player_round.unwrap();
+    let symbol = game.default_symbol();
+    game.play_symbol(coord, symbol).resolve_move(coord);
+    record_move(storage, env, game_id, player, symbol, coord)
+}
+
+/**
+    Pays the prize of a completed game against the house to
+    the player when it won, or returns it to the bankroll when
+    it lost. On draws the player gets its stake back and the
+    reserved winnings return to the bankroll.
+*/
+fn settle_house_game(storage: &mut dyn Storage, game: &Game) -> StdResult<Vec<BankMsg>> {
+    let stake = game.house_stake.clone().unwrap_or_default();
+    let prize = game.prize.first().cloned().unwrap_or_default();
+    let (payout, returned) = match game.winner {
+        Some(winner) if winner == game.host_symbol => (prize.amount, Uint128::zero()),
+        Some(_) => (Uint128::zero(), prize.amount),
+        None => (stake.amount, prize.amount - stake.amount),
+    };
+
+    HOUSE_BANKROLL.update(storage, &prize.denom, |bankroll| -> StdResult<_> {
+        Ok(bankroll.unwrap_or_default() + returned)
+    })?;
+    if payout.is_zero() {
+        return Ok(vec![]);
+    }
+
+    let message = BankMsg::Send {
+        to_address: game.host.to_string(),
+        amount: vec![Coin {
+            denom: prize.denom,
+            amount: payout,
+        }],
+    };
+    if game.winner.is_some() {
+        record_winnings(storage, slice::from_ref(&message))?;
+    }
+    Ok(vec![message])
+}
+
+fn revealing_game(storage: &dyn Storage, game_id: u64) -> StdResult<Game> {
+    games()
+        .may_load(storage, game_id)?
+        .filter(|game| game.status == Status::REVEALING)
+        .ok_or_else(|| StdError::not_found(format!("revealing game {}", game_id)))
+}
+
+fn invited_series(storage: &dyn Storage, series_id: u64) -> StdResult<Series> {
+    SERIES
+        .may_load(storage, series_id)?
+        .filter(|series| series.status == Status::INVITED)
+        .ok_or_else(|| StdError::not_found(format!("invited series {}", series_id)))
+}
+
+/**
+    Starts the next game of a series and adds
+    it to the games played on the series.
+*/
+fn start_series_game(
+    storage: &mut dyn Storage,
+    env: &Env,
+    series_id: u64,
+    series: &mut Series,
+) -> Result<u64, ContractError> {
+    let game_id = start_competition_game(
+        storage,
+        env,
+        series.ruleset,
+        &series.host,
+        &series.opponent,
+        series.next_first_player(),
+        |game| game.series = Some(series_id),
+    )?;
+    series.game_ids.push(game_id);
+
+    Ok(game_id)
+}
+
+/**
+    Scores a completed game of a series and either starts the
+    next game or pays the whole stake once the series is decided,
+    splitting it when the series ends in a draw.
+*/
+fn advance_series(
+    storage: &mut dyn Storage,
+    env: &Env,
+    series_id: u64,
+    game: &Game,
+) -> Result<(Series, Vec<BankMsg>), ContractError> {
+    let mut series = SERIES.load(storage, series_id)?;
+    series.score(game);
+
+    let mut messages = vec![];
+    if series.is_decided() {
+        series.status = Status::COMPLETED;
+        series.winner = series.leader().cloned();
+        match &series.winner {
+            Some(winner) => {
+                messages.push(BankMsg::Send {
+                    to_address: winner.to_string(),
+                    amount: take_fee(storage, None, series.prize.clone())?,
+                });
+                record_winnings(storage, &messages)?;
+            }
+            None => {
+                let prize = take_fee(storage, None, series.get_half_prize())?;
+                for player in [&series.host, &series.opponent] {
+                    messages.push(BankMsg::Send {
+                        to_address: player.to_string(),
+                        amount: prize.clone(),
+                    });
+                }
+            }
+        }
+    } else {
+        start_series_game(storage, env, series_id, &mut series)?;
+    }
+    SERIES.save(storage, series_id, &series)?;
+
+    Ok((series, messages))
+}
+
+fn post_open_invite(storage: &mut dyn Storage, invite: &OpenInvite) -> StdResult<u64> {
+    let invite_id = OPEN_INVITES_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    OPEN_INVITES_COUNT.save(storage, &invite_id)?;
+    open_invites().save(storage, invite_id, invite)?;
+
+    Ok(invite_id)
+}
+
+/**
+    Replaces an open invite by a game between its host and
+    the player accepting it, who already matched the stake.
+*/
+fn join_open_invite(
+    storage: &mut dyn Storage,
+    env: &Env,
+    invite_id: u64,
+    invite: OpenInvite,
+    opponent: Addr,
+    coord: Option<Coord>,
+) -> Result<u64, ContractError> {
+    let mut game = Game::new(
+        invite.host.clone(),
+        opponent,
+        invite.ruleset,
+        invite.first_player,
+        None,
+        vec![invite.stake.clone()],
+    );
+    validate_accept_coord(&mut game, coord)?;
+
+    let game_id = start_game(storage, &game.host, &game.opponent)?;
+    game.status = Status::PLAYING;
+    begin_game(storage, env, game_id, &mut game, vec![invite.stake], coord)?;
+    open_invites().remove(storage, invite_id)?;
+
+    Ok(game_id)
+}
+
+/**
+    The opponent can only play on accepting when it moves first
+    and the first player is not drawn on accepting.
+*/
+fn validate_accept_coord(game: &mut Game, coord: Option<Coord>) -> Result<(), ContractError> {
+    if let Some(coord) = coord {
+        if game.first_player == FirstPlayer::Random || game.already_played(false) {
+            return Err(ContractError::FirstMoveNotAllowed {});
+        } else if !game.ruleset.is_valid_coord(coord) {
+            return Err(ContractError::InvalidCoord { coord });
+        } else if game.already_played_on(coord) {
+            return Err(ContractError::CoordinateAlreadyPlayed { coord });
+        } else if !game.is_playable(coord) {
+            return Err(ContractError::SubBoardNotPlayable { coord });
+        }
+    }
+    Ok(())
+}
+
+/**
+    Completes the acceptance of a game once the stake of the
+    opponent is received, playing the move of the opponent.
+*/
+fn begin_game(
+    storage: &mut dyn Storage,
+    env: &Env,
+    game_id: u64,
+    game: &mut Game,
+    stake: Vec<Coin>,
+    coord: Option<Coord>,
+) -> StdResult<()> {
+    update_stats(storage, &game.host, |stats| {
+        stats.add_staked(&game.host_stake)
+    })?;
+    update_stats(storage, &game.opponent, |stats| stats.add_staked(&stake))?;
+    game.add_opponent_stake(stake);
+    if let Some(coord) = coord {
+        let player = game.player_round.unwrap();
+        let symbol = game.default_symbol();
+        game.play(coord).finish_round();
+        record_move(storage, env, game_id, player, symbol, coord)?;
+    }
+
+    games().save(storage, game_id, game)
+}
+
+fn open_tournament(storage: &dyn Storage, tournament_id: u64) -> StdResult<Tournament> {
+    TOURNAMENTS
+        .may_load(storage, tournament_id)?
+        .filter(|tournament| tournament.status == Status::INVITED)
+        .ok_or_else(|| StdError::not_found(format!("open tournament {}", tournament_id)))
+}
+
+fn open_league(storage: &dyn Storage, league_id: u64) -> StdResult<League> {
+    LEAGUES
+        .may_load(storage, league_id)?
+        .filter(|league| league.status == Status::INVITED)
+        .ok_or_else(|| StdError::not_found(format!("open league {}", league_id)))
+}
+
+/**
+    Starts a game for every pair of players of the league,
+    and a second one with the other player moving first
+    on double round-robin leagues.
+*/
+fn schedule_league(
+    storage: &mut dyn Storage,
+    env: &Env,
+    league_id: u64,
+    league: &mut League,
+) -> StdResult<()> {
+    let players = league.players();
+    record_entry_fees(storage, &players, &league.entry_fee)?;
+    for (index, host) in players.iter().enumerate() {
+        for opponent in &players[index + 1..] {
+            start_competition_game(
+                storage,
+                env,
+                league.ruleset,
+                host,
+                opponent,
+                FirstPlayer::Host,
+                |game| game.league = Some(league_id),
+            )?;
+            league.games_left += 1;
+            if league.double_round_robin {
+                start_competition_game(
+                    storage,
+                    env,
+                    league.ruleset,
+                    opponent,
+                    host,
+                    FirstPlayer::Host,
+                    |game| game.league = Some(league_id),
+                )?;
+                league.games_left += 1;
+            }
+        }
+    }
+    league.status = Status::PLAYING;
+    Ok(())
+}
+
+/**
+    Scores a completed game of a league, paying the prize
+    pool by the final standings once every game is played.
+*/
+fn advance_league(
+    storage: &mut dyn Storage,
+    league_id: u64,
+    game: &Game,
+) -> StdResult<(League, Vec<BankMsg>)> {
+    let mut league = LEAGUES.load(storage, league_id)?;
+    league.score(game);
+
+    let mut messages = vec![];
+    if league.games_left == 0 {
+        let ranking = league.ranking();
+        league.status = Status::COMPLETED;
+        league.winner = ranking.first().map(|standing| standing.player.clone());
+        messages = split_prize_pool(
+            take_fee(storage, None, vec![league.prize_pool()])?.remove(0),
+            ranking.iter().map(|standing| &standing.player).collect(),
+            &league.payout_split,
+        );
+        record_winnings(storage, &messages)?;
+    }
+    LEAGUES.save(storage, league_id, &league)?;
+
+    Ok((league, messages))
+}
+
+/**
+    Starts the first round of a tournament with the
+    players that joined, in their join order.
+*/
+fn start_tournament(
+    storage: &mut dyn Storage,
+    env: &Env,
+    tournament_id: u64,
+    tournament: &mut Tournament,
+) -> StdResult<()> {
+    let seeds = tournament.players.clone();
+    record_entry_fees(storage, &seeds, &tournament.entry_fee)?;
+    tournament.status = Status::PLAYING;
+    start_round(storage, env, tournament_id, tournament, seeds)
+}
+
+/**
+    Pairs the players of a round by seed, the best against the
+    worst. When they are not a power of two the best seeds get
+    a bye, so every later round is complete.
+*/
+fn start_round(
+    storage: &mut dyn Storage,
+    env: &Env,
+    tournament_id: u64,
+    tournament: &mut Tournament,
+    seeds: Vec<Addr>,
+) -> StdResult<()> {
+    let byes = seeds.len().next_power_of_two() - seeds.len();
+    let (byes, players) = seeds.split_at(byes);
+
+    tournament.round += 1;
+    tournament.byes = byes.to_vec();
+    tournament.matches = vec![];
+    for index in 0..players.len() / 2 {
+        let host = players[index].clone();
+        let opponent = players[players.len() - 1 - index].clone();
+        let game_id = start_competition_game(
+            storage,
+            env,
+            tournament.ruleset,
+            &host,
+            &opponent,
+            FirstPlayer::Host,
+            |game| game.tournament = Some(tournament_id),
+        )?;
+        tournament.matches.push(TournamentMatch {
+            host,
+            opponent,
+            game_id,
+            draws: 0,
+            winner: None,
+        });
+    }
+    Ok(())
+}
+
+/**
+    Starts an already accepted game of a series, tournament or
+    league, without prize since it holds the stakes, where
+    set_competition links the game to it. These games are not
+    limited by max_open_games_per_pair.
+*/
+fn start_competition_game(
+    storage: &mut dyn Storage,
+    env: &Env,
+    ruleset: Ruleset,
+    host: &Addr,
+    opponent: &Addr,
+    first_player: FirstPlayer,
+    set_competition: impl FnOnce(&mut Game),
+) -> StdResult<u64> {
+    let game_id = open_new_game(storage, host, opponent)?;
+    let mut game = Game::new(
+        host.clone(),
+        opponent.clone(),
+        ruleset,
+        first_player,
+        None,
+        vec![],
+    );
+    game.status = Status::PLAYING;
+    set_competition(&mut game);
+    game.turn_started = Some(env.block.time);
+    games().save(storage, game_id, &game)?;
+
+    Ok(game_id)
+}
+
+/**
+    Records the result of a completed game of a tournament.
+    Drawn games are replayed swapping the first player, up
+    to MAX_TOURNAMENT_DRAWS draws, after which the host of
+    the match advances. Once every match of the round has a
+    winner the next round starts, or the prize pool is paid
+    when a single player is left.
+*/
+fn advance_tournament(
+    storage: &mut dyn Storage,
+    env: &Env,
+    tournament_id: u64,
+    game_id: u64,
+    game: &Game,
+) -> Result<(Tournament, Vec<BankMsg>), ContractError> {
+    let mut tournament = TOURNAMENTS.load(storage, tournament_id)?;
+    let ruleset = tournament.ruleset;
+    let game_match = tournament
+        .matches
+        .iter_mut()
+        .find(|game_match| game_match.game_id == game_id)
+        .ok_or_else(|| StdError::not_found(format!("match of game {}", game_id)))?;
+
+    match game.winner {
+        Some(winner) => {
+            game_match.winner = Some(game.player_of(winner).clone());
+            tournament
+                .eliminated
+                .push(game.player_of(winner.opposite()).clone());
+        }
+        None if game_match.draws + 1 == MAX_TOURNAMENT_DRAWS => {
+            let loser = game_match.opponent.clone();
+            game_match.draws += 1;
+            game_match.winner = Some(game_match.host.clone());
+            tournament.eliminated.push(loser);
+        }
+        None => {
+            game_match.draws += 1;
+            let first_player = if game.host_symbol == PlayerSymbol::X {
+                FirstPlayer::Opponent
+            } else {
+                FirstPlayer::Host
+            };
+            game_match.game_id = start_competition_game(
+                storage,
+                env,
+                ruleset,
+                &game.host,
+                &game.opponent,
+                first_player,
+                |game| game.tournament = Some(tournament_id),
+            )?;
+        }
+    }
+
+    let mut messages = vec![];
+    if tournament.is_round_complete() {
+        let advancing = tournament.advancing();
+        if advancing.len() == 1 {
+            tournament.status = Status::COMPLETED;
+            tournament.winner = advancing.into_iter().next();
+            messages = split_prize_pool(
+                take_fee(storage, None, vec![tournament.prize_pool()])?.remove(0),
+                tournament.placements(),
+                &tournament.payout_split,
+            );
+            record_winnings(storage, &messages)?;
+        } else {
+            start_round(storage, env, tournament_id, &mut tournament, advancing)?;
+        }
+    }
+    TOURNAMENTS.save(storage, tournament_id, &tournament)?;
+
+    Ok((tournament, messages))
+}
+
+/**
+    Describes why an entry fee is invalid, if it is. Its denom
+    and amount are then checked like stakes, against the limits
+    set by the admin.
+*/
+fn entry_fee_error(entry_fee: &Coin) -> Option<String> {
+    if entry_fee.amount.is_zero() {
+        Some(String::from("entry_fee must not be zero"))
+    } else {
+        None
+    }
+}
+
+/**
+    Describes why a payout split is invalid for a
+    competition of up to max_players, if it is.
+*/
+fn payout_split_error(payout_split: &[u8], max_players: u32) -> Option<String> {
+    if payout_split.is_empty() || payout_split.len() > max_players as usize {
+        Some(String::from(
+            "payout_split must have between 1 and max_players shares",
+        ))
+    } else if payout_split.iter().map(|share| *share as u32).sum::<u32>() != 100 {
+        Some(String::from("payout_split must add up to 100"))
+    } else {
+        None
+    }
+}
+
+/**
+    Splits the prize pool by final placement. What rounding
+    leaves, or the shares of placements nobody reached,
+    goes to the winner.
+*/
+fn split_prize_pool(pool: Coin, placements: Vec<&Addr>, payout_split: &[u8]) -> Vec<BankMsg> {
+    let mut payouts = prize_shares(&pool, placements, payout_split);
+    let paid: Uint128 = payouts.iter().map(|(_, amount)| *amount).sum();
+    if let Some((_, amount)) = payouts.first_mut() {
+        *amount += pool.amount - paid;
+    }
+
+    payout_messages(&pool.denom, payouts)
+}
+
+/**
+    Share of the pool of each placement, leaving out the shares
+    of the split without placement and the rounding remainder.
+*/
+fn prize_shares(pool: &Coin, placements: Vec<&Addr>, payout_split: &[u8]) -> Vec<(Addr, Uint128)> {
+    placements
+        .into_iter()
+        .zip(payout_split)
+        .map(|(player, share)| {
+            (
+                player.clone(),
+                pool.amount.multiply_ratio(*share as u128, 100u128),
+            )
+        })
+        .collect()
+}
+
+fn payout_messages(denom: &str, payouts: Vec<(Addr, Uint128)>) -> Vec<BankMsg> {
+    payouts
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(player, amount)| BankMsg::Send {
+            to_address: player.to_string(),
+            amount: vec![Coin {
+                denom: denom.to_string(),
+                amount,
+            }],
+        })
+        .collect()
+}
+
+/**
+    Updates the stats of both players of a completed game,
+    and their ratings and season scores unless the game was
+    played against the house.
+*/
+fn record_completed_game(storage: &mut dyn Storage, env: &Env, game: &Game) -> StdResult<()> {
+    record_result(storage, game)?;
+    if game.house_stake.is_some() {
+        return Ok(());
+    }
+    rate_game(storage, game)?;
+    score_season(storage, env, game)
+}
+
+/**
+    Updates the Elo rating of both players of a completed game.
+*/
+fn rate_game(storage: &mut dyn Storage, game: &Game) -> StdResult<()> {
+    let host_rating = ratings().may_load(storage, &game.host)?.unwrap_or_default();
+    let opponent_rating = ratings()
+        .may_load(storage, &game.opponent)?
+        .unwrap_or_default();
+    let host_score = match game.winner {
+        Some(winner) if winner == game.host_symbol => 1000,
+        Some(_) => 0,
+        None => 500,
+    };
+
+    let mut rating = host_rating.clone();
+    rating.update(&opponent_rating, host_score);
+    ratings().save(storage, &game.host, &rating)?;
+    let mut rating = opponent_rating;
+    rating.update(&host_rating, 1000 - host_score);
+    ratings().save(storage, &game.opponent, &rating)
+}
+
+fn update_stats(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    action: impl FnOnce(&mut PlayerStats),
+) -> StdResult<PlayerStats> {
+    PLAYER_STATS.update(storage, player, |stats| -> StdResult<_> {
+        let mut stats = stats.unwrap_or_default();
+        action(&mut stats);
+        Ok(stats)
+    })
+}
+
+/**
+    Counts a completed game on the stats of both players,
+    leaving out the house on games against it.
+*/
+fn record_result(storage: &mut dyn Storage, game: &Game) -> StdResult<()> {
+    update_stats(storage, &game.host, |stats| {
+        stats.record_game(game.winner.map(|winner| winner == game.host_symbol))
+    })?;
+    if game.house_stake.is_some() {
+        return Ok(());
+    }
+    update_stats(storage, &game.opponent, |stats| {
+        stats.record_game(game.winner.map(|winner| winner != game.host_symbol))
+    })?;
+    Ok(())
+}
+
+/**
+    Adds the prizes sent to the winners to their stats.
+*/
+fn record_winnings(storage: &mut dyn Storage, messages: &[BankMsg]) -> StdResult<()> {
+    for message in messages {
+        if let BankMsg::Send { to_address, amount } = message {
+            update_stats(storage, &Addr::unchecked(to_address), |stats| {
+                stats.add_won(amount)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn record_entry_fees(
+    storage: &mut dyn Storage,
+    players: &[Addr],
+    entry_fee: &Coin,
+) -> StdResult<()> {
+    for player in players {
+        update_stats(storage, player, |stats| {
+            stats.add_staked(slice::from_ref(entry_fee))
+        })?;
+    }
+    Ok(())
+}
+
+/**
+    Awards the points of a completed game to both players
+    on the season active at the current block, if any.
+    Seasons never overlap, so only the latest season that
+    already started can be active.
+*/
+fn score_season(storage: &mut dyn Storage, env: &Env, game: &Game) -> StdResult<()> {
+    let season = SEASONS
+        .range(storage, None, None, Order::Descending)
+        .find(|record| match record {
+            Ok((_, season)) => season.start_time <= env.block.time,
+            Err(_) => true,
+        })
+        .transpose()?
+        .filter(|(_, season)| season.is_active_at(env.block.time));
+
+    if let Some((season_id, season)) = season {
+        for player in [&game.host, &game.opponent] {
+            let scores = season_scores();
+            let mut score = scores
+                .may_load(storage, (season_id, player))?
+                .unwrap_or_else(|| SeasonScore {
+                    season_id,
+                    standing: LeagueStanding::new(player.clone()),
+                });
+            score.standing.record(
+                game.winner.map(|winner| game.player_of(winner) == player),
+                &season.points,
+            );
+            scores.save(storage, (season_id, player), &score)?;
+        }
+    }
+    Ok(())
+}
+
+/**
+    Reserves the id of a new game between both players,
+    as long as the pair has not reached its open games limit.
+*/
+fn start_game(
+    storage: &mut dyn Storage,
+    host: &Addr,
+    opponent: &Addr,
+) -> Result<u64, ContractError> {
+    if !can_start_game(storage, host, opponent)? {
+        return Err(ContractError::GameAlreadyInProgress {
+            host: host.clone(),
+            opponent: opponent.clone(),
+        });
+    }
+
+    Ok(open_new_game(storage, host, opponent)?)
+}
+
+fn open_new_game(storage: &mut dyn Storage, host: &Addr, opponent: &Addr) -> StdResult<u64> {
+    let mut game_id: u64 = GAMES_COUNT.load(storage)?;
+    game_id = game_id + 1;
+    GAMES_COUNT.save(storage, &game_id)?;
+    open_game(storage, host, opponent, game_id)?;
+
+    Ok(game_id)
+}
+
+/**
+    Moves the games of version 0.1.0 to the games map, keeping
+    their ids, and opens the ones still invited or playing.
+*/
+pub fn migrate_legacy_games(storage: &mut dyn Storage) -> StdResult<usize> {
+    let legacy_games = LEGACY_GAMES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for ((host, opponent, game_id), legacy_game) in &legacy_games {
+        let game = legacy_game
+            .clone()
+            .into_game(host.clone(), opponent.clone());
+        if matches!(game.status, Status::INVITED | Status::PLAYING) {
+            open_game(storage, host, opponent, *game_id)?;
+        }
+        games().save(storage, *game_id, &game)?;
+        LEGACY_GAMES.remove(storage, (host, opponent, *game_id));
+    }
+
+    Ok(legacy_games.len())
+}
+
+fn can_start_game(storage: &dyn Storage, host: &Addr, opponent: &Addr) -> StdResult<bool> {
+    let config = CONFIG.load(storage)?;
+    let open_games = OPEN_GAMES
+        .may_load(storage, pair_key(host, opponent))?
+        .unwrap_or_default();
+
+    Ok(open_games.len() < config.max_open_games_per_pair as usize)
+}
+
+fn open_game(
+    storage: &mut dyn Storage,
+    host: &Addr,
+    opponent: &Addr,
+    game_id: u64,
+) -> StdResult<()> {
+    OPEN_GAMES.update(
+        storage,
+        pair_key(host, opponent),
+        |game_ids| -> StdResult<_> {
+            let mut game_ids = game_ids.unwrap_or_default();
+            game_ids.push(game_id);
+            Ok(game_ids)
+        },
+    )?;
+    Ok(())
+}
+
+fn close_game(
+    storage: &mut dyn Storage,
+    host: &Addr,
+    opponent: &Addr,
+    game_id: u64,
+) -> StdResult<()> {
+    let key = pair_key(host, opponent);
+    let mut game_ids = OPEN_GAMES.may_load(storage, key)?.unwrap_or_default();
+    game_ids.retain(|open_game_id| *open_game_id != game_id);
+
+    if game_ids.is_empty() {
+        OPEN_GAMES.remove(storage, key);
+    } else {
+        OPEN_GAMES.save(storage, key, &game_ids)?;
+    }
+    Ok(())
+}
+
+/**
+    Normalizes the coins staked by a player, which must be of a
+    single denom within the limits set by the admin, if any. Games
+    without stake are only allowed while stakes are not limited.
+*/
+fn validate_stake(storage: &dyn Storage, funds: &[Coin]) -> Result<Vec<Coin>, ContractError> {
+    let stake = normalize_coins(funds);
+    if stake.len() > 1 {
+        return Err(ContractError::MultipleStakeDenoms {});
+    }
+
+    let config = CONFIG.load(storage)?;
+    if !config.stake_limits.is_empty() {
+        let coin = stake.first().ok_or(ContractError::StakeRequired {})?;
+        let limit = config
+            .stake_limits
+            .iter()
+            .find(|limit| limit.denom == coin.denom)
+            .ok_or_else(|| ContractError::StakeDenomNotAllowed {
+                denom: coin.denom.clone(),
+            })?;
+        if coin.amount < limit.min {
+            return Err(ContractError::StakeTooLow {
+                min: Coin {
+                    denom: coin.denom.clone(),
+                    amount: limit.min,
+                },
+            });
+        } else if let Some(max) = limit.max.filter(|max| coin.amount > *max) {
+            return Err(ContractError::StakeTooHigh {
+                max: Coin {
+                    denom: coin.denom.clone(),
+                    amount: max,
+                },
+            });
+        }
+    }
+    Ok(stake)
+}
+
+/**
+    Deducts the platform fee from coins of a prize, staked with
+    the given CW20 token if any, adding it to the fees collected
+    for the treasury, and returns what is left to pay out.
+*/
+fn take_fee(
+    storage: &mut dyn Storage,
+    token: Option<&Addr>,
+    mut prize: Vec<Coin>,
+) -> StdResult<Vec<Coin>> {
+    let fee_bps = CONFIG.load(storage)?.fee_bps;
+    for coin in prize.iter_mut() {
+        let fee = coin.amount.multiply_ratio(fee_bps, MAX_BPS);
+        if fee.is_zero() {
+            continue;
+        }
+        let add_fee =
+            |fees: Option<Uint128>| -> StdResult<Uint128> { Ok(fees.unwrap_or_default() + fee) };
+        match token {
+            Some(token) => TOKEN_FEES.update(storage, token, add_fee)?,
+            None => FEES.update(storage, &coin.denom, add_fee)?,
+        };
+        coin.amount -= fee;
+    }
+    Ok(prize)
+}
+
+/**
+    Sends coins of the prize of a game, through its
+    token contract when the game is staked with CW20.
+*/
+fn prize_message(game: &Game, to_address: &Addr, amount: Vec<Coin>) -> StdResult<CosmosMsg> {
+    match &game.token {
+        Some(token) => Ok(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: to_address.to_string(),
+                amount: amount.iter().map(|coin| coin.amount).sum(),
+            })?,
+            funds: vec![],
+        }
+        .into()),
+        None => Ok(BankMsg::Send {
+            to_address: to_address.to_string(),
+            amount,
+        }
+        .into()),
+    }
+}
+
+/**
+    Transfers the stakes escrowed on an NFT wager to the winner,
+    or back to the player that escrowed each one without winner.
+*/
+fn nft_wager_messages(
+    game: &Game,
+    nft_wager: &NftWager,
+    winner: Option<&Addr>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let opponent_recipient = winner.unwrap_or(&game.opponent);
+    let mut messages = vec![nft_transfer_message(
+        &nft_wager.host_nft,
+        winner.unwrap_or(&game.host),
+    )?];
+    if let Some(nft) = &nft_wager.opponent_nft {
+        messages.push(nft_transfer_message(nft, opponent_recipient)?);
+    }
+    if !nft_wager.opponent_funds.is_empty() {
+        messages.push(
+            BankMsg::Send {
+                to_address: opponent_recipient.to_string(),
+                amount: nft_wager.opponent_funds.clone(),
+            }
+            .into(),
+        );
+    }
+    Ok(messages)
+}
+
+fn nft_transfer_message(nft: &Nft, recipient: &Addr) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: nft.collection.to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: nft.token_id.clone(),
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+fn coord_attributes(coord: Option<Coord>) -> Vec<(&'static str, String)> {
+    coord
+        .map(|coord| {
+            let mut attributes = vec![("x", coord.x.to_string()), ("y", coord.y.to_string())];
+            attributes.extend(coord.z.map(|z| ("z", z.to_string())));
+            attributes
+        })
+        .unwrap_or_default()
+}
+
+/**
+    Appends a move to the history of the game,
+    indexed by the order in which it was played.
+*/
+fn record_move(
+    storage: &mut dyn Storage,
+    env: &Env,
+    game_id: u64,
+    player: PlayerSymbol,
+    symbol: PlayerSymbol,
+    coord: Coord,
+) -> StdResult<()> {
+    let move_index = match MOVES
+        .prefix(game_id)
+        .keys(storage, None, None, Order::Descending)
+        .next()
+    {
+        Some(last_index) => last_index? + 1,
+        None => 0,
+    };
+
+    MOVES.save(
+        storage,
+        (game_id, move_index),
+        &Move {
+            player,
+            coord,
+            symbol: Some(symbol).filter(|symbol| *symbol != player),
+            height: env.block.height,
+            time: env.block.time,
+        },
+    )
+}
@@ -0,0 +1,107 @@
+use crate::state::{Coord, PlayerSymbol};
+
+const SIZE: usize = 3;
+const CELLS: usize = SIZE * SIZE;
+
+/**
+    Rows, columns and diagonals of a classic board,
+    as indexes of its cells.
+*/
+const LINES: [[usize; SIZE]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+type Cells = [Option<PlayerSymbol>; CELLS];
+
+/**
+    Move of the house on a classic board of size 3, chosen
+    by searching every remaining game with minimax. Faster
+    wins and slower losses are preferred, so the house never
+    loses and wins whenever the player makes a mistake.
+*/
+pub fn best_move(board: &[Vec<Option<PlayerSymbol>>], symbol: PlayerSymbol) -> Option<Coord> {
+    let mut cells = cells_of(board);
+    empty_cells(&cells)
+        .into_iter()
+        .max_by_key(|cell| {
+            cells[*cell] = Some(symbol);
+            let score = -minimax(&mut cells, symbol.opposite(), 1);
+            cells[*cell] = None;
+            score
+        })
+        .map(coord_of)
+}
+
+/**
+    Move of the house on a classic board of size 3, chosen
+    among the empty cells with the given seed.
+*/
+pub fn random_move(board: &[Vec<Option<PlayerSymbol>>], seed: u64) -> Option<Coord> {
+    let empty_cells = empty_cells(&cells_of(board));
+    if empty_cells.is_empty() {
+        return None;
+    }
+
+    Some(coord_of(
+        empty_cells[(seed % empty_cells.len() as u64) as usize],
+    ))
+}
+
+/**
+    Score of the position for the player to move, positive
+    when it wins and negative when it loses, greater the
+    sooner the game is decided.
+*/
+fn minimax(cells: &mut Cells, to_move: PlayerSymbol, depth: i32) -> i32 {
+    if has_line(cells, to_move.opposite()) {
+        return depth - CELLS as i32 - 1;
+    }
+
+    let empty_cells = empty_cells(cells);
+    if empty_cells.is_empty() {
+        return 0;
+    }
+
+    let mut best = i32::MIN;
+    for cell in empty_cells {
+        cells[cell] = Some(to_move);
+        best = best.max(-minimax(cells, to_move.opposite(), depth + 1));
+        cells[cell] = None;
+    }
+    best
+}
+
+fn has_line(cells: &Cells, symbol: PlayerSymbol) -> bool {
+    LINES
+        .iter()
+        .any(|line| line.iter().all(|cell| cells[*cell] == Some(symbol)))
+}
+
+fn empty_cells(cells: &Cells) -> Vec<usize> {
+    (0..CELLS).filter(|cell| cells[*cell].is_none()).collect()
+}
+
+fn cells_of(board: &[Vec<Option<PlayerSymbol>>]) -> Cells {
+    let mut cells = [None; CELLS];
+    for (row, symbols) in board.iter().take(SIZE).enumerate() {
+        for (column, symbol) in symbols.iter().take(SIZE).enumerate() {
+            cells[row * SIZE + column] = *symbol;
+        }
+    }
+    cells
+}
+
+fn coord_of(cell: usize) -> Coord {
+    Coord {
+        x: (cell % SIZE) as u8,
+        y: (cell / SIZE) as u8,
+        z: None,
+    }
+}
@@ -2,6 +2,7 @@ pub mod contract;
 mod data;
 mod errors;
 mod execution;
+mod house;
 mod msg;
 mod query;
 mod state;
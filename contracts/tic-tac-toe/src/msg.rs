@@ -1,17 +1,39 @@
+use cosmwasm_std::{Binary, Coin, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw721::Cw721ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::{Coord, Status};
+use crate::state::{
+    Coord, FirstPlayer, HouseConfig, LeaguePoints, PlayerSymbol, Ruleset, StakeLimit, Status,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InstantiateMsg {}
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub max_open_games_per_pair: Option<u32>,
+    pub reveal_timeout: Option<u64>,
+    pub treasury: Option<String>,
+    pub fee_bps: Option<u32>,
+}
+
+/**
+    The admin is only read when migrating from version 0.1.0,
+    which had no config to keep it.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    pub admin: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Invite {
-        coord: Coord,
+        coord: Option<Coord>,
         opponent: String,
+        ruleset: Option<Ruleset>,
+        first_player: Option<FirstPlayer>,
+        commitment: Option<Binary>,
     },
     Reject {
         as_host: bool,
@@ -19,15 +41,161 @@ pub enum ExecuteMsg {
         game_id: u64,
     },
     Accept {
-        coord: Coord,
+        coord: Option<Coord>,
         host: String,
         game_id: u64,
+        nonce: Option<Binary>,
+    },
+    Reveal {
+        game_id: u64,
+        secret: Binary,
+    },
+    ClaimTimeout {
+        game_id: u64,
+    },
+    Rematch {
+        game_id: u64,
+    },
+    CreateSeries {
+        opponent: String,
+        best_of: u8,
+        ruleset: Option<Ruleset>,
+        move_timeout: Option<u64>,
+    },
+    AcceptSeries {
+        series_id: u64,
+    },
+    RejectSeries {
+        series_id: u64,
+    },
+    OpenInvite {
+        ruleset: Option<Ruleset>,
+        first_player: Option<FirstPlayer>,
+        allow_list: Option<Vec<String>>,
+    },
+    AcceptOpenInvite {
+        invite_id: u64,
+        coord: Option<Coord>,
+    },
+    CancelOpenInvite {
+        invite_id: u64,
+    },
+    AutoMatch {
+        stake: Coin,
+    },
+    CreateTournament {
+        entry_fee: Coin,
+        max_players: u32,
+        start_time: Timestamp,
+        payout_split: Vec<u8>,
+        ruleset: Option<Ruleset>,
+        move_timeout: Option<u64>,
+    },
+    JoinTournament {
+        tournament_id: u64,
+    },
+    StartTournament {
+        tournament_id: u64,
+    },
+    CreateLeague {
+        entry_fee: Coin,
+        max_players: u32,
+        start_time: Timestamp,
+        double_round_robin: bool,
+        points: Option<LeaguePoints>,
+        payout_split: Vec<u8>,
+        ruleset: Option<Ruleset>,
+        move_timeout: Option<u64>,
+    },
+    JoinLeague {
+        league_id: u64,
+    },
+    StartLeague {
+        league_id: u64,
+    },
+    CreateSeason {
+        start_time: Timestamp,
+        end_time: Timestamp,
+        payout_split: Vec<u8>,
+        points: Option<LeaguePoints>,
+    },
+    FinalizeSeason {
+        season_id: u64,
+    },
+    PlayHouse {
+        coord: Coord,
+        stake: Option<Coin>,
+    },
+    FundHouse {},
+    WithdrawHouse {
+        amount: Coin,
     },
     Play {
         as_host: bool,
         coord: Coord,
         opponent: String,
         game_id: u64,
+        symbol: Option<PlayerSymbol>,
+    },
+    UpdateConfig {
+        max_open_games_per_pair: Option<u32>,
+        reveal_timeout: Option<u64>,
+        house: Option<HouseConfig>,
+        treasury: Option<String>,
+        fee_bps: Option<u32>,
+        stake_limits: Option<Vec<StakeLimit>>,
+    },
+    WithdrawFees {},
+    Receive(Cw20ReceiveMsg),
+    ReceiveNft(Cw721ReceiveMsg),
+}
+
+/**
+    Messages embedded on CW20 transfers to the contract,
+    staking the tokens sent instead of native funds.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Invite {
+        coord: Option<Coord>,
+        opponent: String,
+        ruleset: Option<Ruleset>,
+        first_player: Option<FirstPlayer>,
+        commitment: Option<Binary>,
+    },
+    Accept {
+        coord: Option<Coord>,
+        host: String,
+        game_id: u64,
+        nonce: Option<Binary>,
+    },
+    Rematch {
+        game_id: u64,
+    },
+}
+
+/**
+    Messages embedded on CW721 transfers to the contract,
+    escrowing the token sent as the stake of the game.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveNftMsg {
+    Invite {
+        coord: Option<Coord>,
+        opponent: String,
+        ruleset: Option<Ruleset>,
+        first_player: Option<FirstPlayer>,
+        commitment: Option<Binary>,
+        allowed_collections: Vec<String>,
+        native_value: Option<Coin>,
+    },
+    Accept {
+        coord: Option<Coord>,
+        host: String,
+        game_id: u64,
+        nonce: Option<Binary>,
     },
 }
 
@@ -35,8 +203,84 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     GetContractVersion {},
-    Game { key: QueryKey, game_id: u64 },
-    Games { status: Option<Status> },
+    Config {},
+    Game {
+        key: QueryKey,
+        game_id: u64,
+    },
+    Games {
+        status: Option<Status>,
+        player: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    PlayerGames {
+        address: String,
+        role: Option<Role>,
+        status: Option<Status>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    ActiveGames {
+        player: String,
+        opponent: String,
+    },
+    GameMoves {
+        game_id: u64,
+    },
+    RematchHistory {
+        game_id: u64,
+    },
+    Series {
+        id: u64,
+    },
+    SeriesGames {
+        id: u64,
+    },
+    Tournament {
+        id: u64,
+    },
+    Standings {
+        league_id: u64,
+    },
+    Season {
+        id: Option<u64>,
+    },
+    SeasonRanking {
+        season_id: Option<u64>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    HouseBankroll {},
+    Fees {},
+    EscrowBalance {},
+    Rating {
+        address: String,
+    },
+    PlayerStats {
+        address: String,
+    },
+    Leaderboard {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Lobby {
+        denom: Option<String>,
+        min_stake: Option<Uint128>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    ReplayAt {
+        game_id: u64,
+        move_index: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Host,
+    Opponent,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
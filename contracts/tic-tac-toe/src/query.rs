@@ -1,55 +1,631 @@
 #[cfg(not(feature = "library"))]
-use crate::data::GameResponse;
-use crate::msg::QueryKey;
-use crate::state::{Status, GAMES};
-use cosmwasm_std::{Deps, Order, StdResult};
+use crate::data::{
+    EscrowBalanceResponse, FeesResponse, GameResponse, OpenInviteResponse, PlayerGamesResponse,
+    PlayerStatsResponse, RatingResponse, ReplayResponse, SeasonRankingResponse, SeasonResponse,
+    SeriesResponse, StandingsResponse, TournamentResponse,
+};
+use crate::msg::{QueryKey, Role};
+use crate::state::{
+    games, normalize_coins, open_invites, pair_key, ratings, season_scores, Game, Move, Season,
+    Status, CONFIG, FEES, HOUSE_BANKROLL, LEAGUES, MOVES, OPEN_GAMES, PLAYER_STATS, REMATCHES,
+    SEASONS, SERIES, TOKEN_FEES, TOURNAMENTS,
+};
+use cosmwasm_std::{Addr, Coin, Deps, Order, StdError, StdResult, Timestamp, Uint128};
+use cw20::Cw20Coin;
+use cw_storage_plus::Bound;
+use std::cmp::Reverse;
 
-pub fn query_game(deps: Deps, key: QueryKey, game_id: u64) -> StdResult<Vec<GameResponse>> {
-    let res: Vec<GameResponse>;
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+type GameRecord = StdResult<(u64, Game)>;
 
+pub fn query_game(deps: Deps, key: QueryKey, game_id: u64) -> StdResult<Vec<GameResponse>> {
     let host_address = deps.api.addr_validate(&key.host)?;
     let opponent_address = deps.api.addr_validate(&key.opponent)?;
 
-    let game_option = GAMES
-        .may_load(deps.storage, (&host_address, &opponent_address, game_id))
-        .unwrap();
+    let res = games()
+        .may_load(deps.storage, game_id)?
+        .filter(|game| game.is_between(&host_address, &opponent_address))
+        .map(|game| GameResponse::new(game_id, game))
+        .into_iter()
+        .collect();
+
+    Ok(res)
+}
+
+pub fn query_active_games(deps: Deps, player: String, opponent: String) -> StdResult<Vec<u64>> {
+    let player_address = deps.api.addr_validate(&player)?;
+    let opponent_address = deps.api.addr_validate(&opponent)?;
+
+    Ok(OPEN_GAMES
+        .may_load(deps.storage, pair_key(&player_address, &opponent_address))?
+        .unwrap_or_default())
+}
+
+pub fn query_games(
+    deps: Deps,
+    status: Option<Status>,
+    player: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<GameResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let games = games();
+
+    let records: Box<dyn Iterator<Item = GameRecord>> = match player {
+        Some(player) => {
+            let player_address = deps.api.addr_validate(&player)?;
+            let (hosted, invited) = match status {
+                Some(status) => {
+                    let key = (player_address, status.to_string());
+                    (
+                        games.idx.host_status.prefix(key.clone()).range(
+                            deps.storage,
+                            start.clone(),
+                            None,
+                            Order::Ascending,
+                        ),
+                        games.idx.opponent_status.prefix(key).range(
+                            deps.storage,
+                            start,
+                            None,
+                            Order::Ascending,
+                        ),
+                    )
+                }
+                None => (
+                    games.idx.host.prefix(player_address.clone()).range(
+                        deps.storage,
+                        start.clone(),
+                        None,
+                        Order::Ascending,
+                    ),
+                    games.idx.opponent.prefix(player_address).range(
+                        deps.storage,
+                        start,
+                        None,
+                        Order::Ascending,
+                    ),
+                ),
+            };
 
-    match game_option {
-        Some(_game) => {
-            res = vec![GameResponse {
-                game: _game,
-                host: host_address,
-                opponent: opponent_address,
-            }]
+            Box::new(merge_by_game_id(hosted, invited, Order::Ascending))
         }
-        None => res = vec![],
+        None => match status {
+            Some(status) => games.idx.status.prefix(status.to_string()).range(
+                deps.storage,
+                start,
+                None,
+                Order::Ascending,
+            ),
+            None => games.range(deps.storage, start, None, Order::Ascending),
+        },
+    };
+
+    records
+        .take(limit)
+        .map(|record| record.map(|(game_id, game)| GameResponse::new(game_id, game)))
+        .collect()
+}
+
+pub fn query_player_games(
+    deps: Deps,
+    address: String,
+    role: Option<Role>,
+    status: Option<Status>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PlayerGamesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let player = deps.api.addr_validate(&address)?;
+    let statuses = match status {
+        Some(status) => vec![status],
+        None => vec![
+            Status::INVITED,
+            Status::PLAYING,
+            Status::REVEALING,
+            Status::COMPLETED,
+            Status::REJECTED,
+        ],
+    };
+
+    let mut res = PlayerGamesResponse::default();
+    for status in statuses {
+        let records = player_games_by_status(deps, &player, role, status, start_after).take(limit);
+
+        match status {
+            Status::INVITED | Status::PLAYING | Status::REVEALING => {
+                for record in records {
+                    let (game_id, game) = record?;
+                    let bucket = if game.status == Status::INVITED && game.opponent == player {
+                        &mut res.invitations
+                    } else if game.is_turn_of(&player) {
+                        &mut res.my_turn
+                    } else {
+                        &mut res.waiting
+                    };
+                    bucket.push(GameResponse::new(game_id, game));
+                }
+            }
+            Status::COMPLETED | Status::REJECTED => {
+                for record in records {
+                    let (game_id, game) = record?;
+                    res.history.push(GameResponse::new(game_id, game));
+                }
+            }
+        }
+    }
+
+    for bucket in [
+        &mut res.invitations,
+        &mut res.my_turn,
+        &mut res.waiting,
+        &mut res.history,
+    ] {
+        bucket.sort_by_key(|game| Reverse(game.game_id));
+        bucket.truncate(limit);
     }
 
     Ok(res)
 }
 
-pub fn query_games(deps: Deps, status: Option<Status>) -> StdResult<Vec<GameResponse>> {
-    let mut res: Vec<GameResponse> = GAMES
+pub fn query_game_moves(deps: Deps, game_id: u64) -> StdResult<Vec<Move>> {
+    MOVES
+        .prefix(game_id)
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|f| {
-            let (addresses, game) = f.unwrap();
+        .map(|record| record.map(|(_, game_move)| game_move))
+        .collect()
+}
+
+pub fn query_replay_at(deps: Deps, game_id: u64, move_index: u32) -> StdResult<ReplayResponse> {
+    let last_move = MOVES
+        .may_load(deps.storage, (game_id, move_index))?
+        .ok_or_else(|| StdError::not_found(format!("move {} of game {}", move_index, game_id)))?;
 
-            GameResponse {
-                game: game,
-                host: addresses.0,
-                opponent: addresses.1,
+    let game = games().load(deps.storage, game_id)?;
+    let mut board = game.ruleset.empty_board();
+    for record in MOVES.prefix(game_id).range(
+        deps.storage,
+        None,
+        Some(Bound::inclusive(move_index)),
+        Order::Ascending,
+    ) {
+        let (_, game_move) = record?;
+        let (row, column) = game.ruleset.cell_of(game_move.coord);
+        board[row][column] = Some(game_move.symbol.unwrap_or(game_move.player));
+    }
+
+    Ok(ReplayResponse {
+        move_index,
+        last_move,
+        board,
+    })
+}
+
+/**
+    Lists every game linked to the given one through
+    rematches, from the first game to the last one.
+*/
+pub fn query_rematch_history(deps: Deps, game_id: u64) -> StdResult<Vec<GameResponse>> {
+    let mut first_game_id = game_id;
+    while let Some(previous_game_id) = games().load(deps.storage, first_game_id)?.previous_game {
+        first_game_id = previous_game_id;
+    }
+
+    let mut history = vec![];
+    let mut next_game_id = Some(first_game_id);
+    while let Some(game_id) = next_game_id {
+        history.push(GameResponse::new(
+            game_id,
+            games().load(deps.storage, game_id)?,
+        ));
+        next_game_id = REMATCHES.may_load(deps.storage, game_id)?;
+    }
+
+    Ok(history)
+}
+
+pub fn query_series(deps: Deps, series_id: u64) -> StdResult<SeriesResponse> {
+    Ok(SeriesResponse {
+        series_id,
+        series: SERIES.load(deps.storage, series_id)?,
+    })
+}
+
+pub fn query_tournament(deps: Deps, tournament_id: u64) -> StdResult<TournamentResponse> {
+    Ok(TournamentResponse {
+        tournament_id,
+        tournament: TOURNAMENTS.load(deps.storage, tournament_id)?,
+    })
+}
+
+pub fn query_standings(deps: Deps, league_id: u64) -> StdResult<StandingsResponse> {
+    let league = LEAGUES.load(deps.storage, league_id)?;
+    Ok(StandingsResponse {
+        league_id,
+        status: league.status,
+        winner: league.winner.clone(),
+        games_left: league.games_left,
+        standings: league.ranking(),
+    })
+}
+
+/**
+    Funds of the house available to back new games.
+*/
+pub fn query_house_bankroll(deps: Deps) -> StdResult<Vec<Coin>> {
+    HOUSE_BANKROLL
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|record| record.map(|(denom, amount)| Coin { denom, amount }))
+        .collect()
+}
+
+/**
+    Reconciles the native coins the contract should hold, from
+    open games, invites, competitions, the house bankroll and
+    collected fees, with its bank balance. It goes through every
+    open escrow, so it is meant for audits rather than clients.
+*/
+pub fn query_escrow_balance(deps: Deps, contract: &Addr) -> StdResult<EscrowBalanceResponse> {
+    let is_open = |status: &Status| matches!(status, Status::INVITED | Status::PLAYING);
+    let mut escrowed = vec![];
+    for status in [Status::INVITED, Status::PLAYING, Status::REVEALING] {
+        for record in games().idx.status.prefix(status.to_string()).range(
+            deps.storage,
+            None,
+            None,
+            Order::Ascending,
+        ) {
+            let (_, game) = record?;
+            if game.token.is_none() {
+                escrowed.extend(game.prize);
+            }
+            if let Some(nft_wager) = game.nft_wager {
+                escrowed.extend(nft_wager.opponent_funds);
             }
+        }
+    }
+    for record in open_invites().range(deps.storage, None, None, Order::Ascending) {
+        escrowed.push(record?.1.stake);
+    }
+    for record in SERIES.range(deps.storage, None, None, Order::Ascending) {
+        let (_, series) = record?;
+        if is_open(&series.status) {
+            escrowed.extend(series.prize);
+        }
+    }
+    for record in TOURNAMENTS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, tournament) = record?;
+        if is_open(&tournament.status) {
+            escrowed.push(tournament.prize_pool());
+        }
+    }
+    for record in LEAGUES.range(deps.storage, None, None, Order::Ascending) {
+        let (_, league) = record?;
+        if is_open(&league.status) {
+            escrowed.push(league.prize_pool());
+        }
+    }
+    for record in SEASONS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, season) = record?;
+        if is_open(&season.status) {
+            escrowed.push(season.prize_pool);
+        }
+    }
+    for map in [HOUSE_BANKROLL, FEES] {
+        for record in map.range(deps.storage, None, None, Order::Ascending) {
+            let (denom, amount) = record?;
+            escrowed.push(Coin { denom, amount });
+        }
+    }
+
+    let escrowed = normalize_coins(&escrowed);
+    let balance = normalize_coins(&deps.querier.query_all_balances(contract)?);
+    let amount_of = |coins: &[Coin], denom: &str| {
+        coins
+            .iter()
+            .find(|coin| coin.denom == denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default()
+    };
+    let difference = |from: &[Coin], to: &[Coin]| {
+        let coins = from
+            .iter()
+            .map(|coin| Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount.saturating_sub(amount_of(to, &coin.denom)),
+            })
+            .collect::<Vec<Coin>>();
+        normalize_coins(&coins)
+    };
+
+    Ok(EscrowBalanceResponse {
+        surplus: difference(&balance, &escrowed),
+        shortfall: difference(&escrowed, &balance),
+        escrowed,
+        balance,
+    })
+}
+
+pub fn query_fees(deps: Deps) -> StdResult<FeesResponse> {
+    Ok(FeesResponse {
+        treasury: CONFIG.load(deps.storage)?.treasury().clone(),
+        native: FEES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|record| record.map(|(denom, amount)| Coin { denom, amount }))
+            .collect::<StdResult<_>>()?,
+        tokens: TOKEN_FEES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|record| {
+                record.map(|(address, amount)| Cw20Coin {
+                    address: address.to_string(),
+                    amount,
+                })
+            })
+            .collect::<StdResult<_>>()?,
+    })
+}
+
+pub fn query_season(
+    deps: Deps,
+    now: Timestamp,
+    season_id: Option<u64>,
+) -> StdResult<SeasonResponse> {
+    let (season_id, season) = load_season(deps, now, season_id)?;
+    Ok(SeasonResponse { season_id, season })
+}
+
+/**
+    Players of a season from the most points to the least,
+    paginated by the address of the last player listed.
+    Players tied on points are listed by descending address.
+*/
+pub fn query_season_ranking(
+    deps: Deps,
+    now: Timestamp,
+    season_id: Option<u64>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SeasonRankingResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (season_id, _) = load_season(deps, now, season_id)?;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let scores = season_scores();
+    let end = match &start_after {
+        Some(address) => {
+            let score = scores.load(deps.storage, (season_id, address))?;
+            Some(Bound::exclusive((
+                score.standing.points,
+                (season_id, address),
+            )))
+        }
+        None => None,
+    };
+
+    let standings = scores
+        .idx
+        .points
+        .sub_prefix(season_id)
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|record| record.map(|(_, score)| score.standing))
+        .collect::<StdResult<_>>()?;
+    Ok(SeasonRankingResponse {
+        season_id,
+        standings,
+    })
+}
+
+/**
+    Loads the given season, or the latest one
+    that started before now when not given.
+*/
+fn load_season(deps: Deps, now: Timestamp, season_id: Option<u64>) -> StdResult<(u64, Season)> {
+    match season_id {
+        Some(season_id) => Ok((season_id, SEASONS.load(deps.storage, season_id)?)),
+        None => SEASONS
+            .range(deps.storage, None, None, Order::Descending)
+            .find(|record| match record {
+                Ok((_, season)) => season.start_time <= now,
+                Err(_) => true,
+            })
+            .unwrap_or_else(|| Err(StdError::not_found("current season"))),
+    }
+}
+
+/**
+    Rating of a player, which is the initial one
+    until the player completes a game.
+*/
+pub fn query_rating(deps: Deps, address: String) -> StdResult<RatingResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let rating = ratings()
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(RatingResponse {
+        address,
+        rating: rating.rating,
+        games: rating.games,
+    })
+}
+
+pub fn query_player_stats(deps: Deps, address: String) -> StdResult<PlayerStatsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let stats = PLAYER_STATS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(PlayerStatsResponse { address, stats })
+}
+
+/**
+    Rated players from the highest rating to the lowest,
+    paginated by the address of the last player listed.
+*/
+pub fn query_leaderboard(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<RatingResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let ratings = ratings();
+    let end = match &start_after {
+        Some(address) => {
+            let rating = ratings.load(deps.storage, address)?;
+            Some(Bound::exclusive((rating.rating, address)))
+        }
+        None => None,
+    };
+
+    ratings
+        .idx
+        .rating
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|record| {
+            record.map(|(address, rating)| RatingResponse {
+                address,
+                rating: rating.rating,
+                games: rating.games,
+            })
         })
-        .collect();
+        .collect()
+}
+
+pub fn query_series_games(deps: Deps, series_id: u64) -> StdResult<Vec<GameResponse>> {
+    SERIES
+        .load(deps.storage, series_id)?
+        .game_ids
+        .into_iter()
+        .map(|game_id| {
+            Ok(GameResponse::new(
+                game_id,
+                games().load(deps.storage, game_id)?,
+            ))
+        })
+        .collect()
+}
 
-    match status {
-        Some(status) => {
-            res = res
-                .into_iter()
-                .filter(|res| res.game.status == status)
-                .collect()
+/**
+    Lists the open invites, oldest first, optionally only those of the given
+    denom. With min_stake, lists the invites staking at least min_stake of
+    the denom from the stake index instead, lowest stake first.
+*/
+pub fn query_lobby(
+    deps: Deps,
+    denom: Option<String>,
+    min_stake: Option<Uint128>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OpenInviteResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let invites = open_invites();
+
+    let records = match (denom, min_stake) {
+        (Some(denom), Some(min_stake)) => {
+            let start = match start_after {
+                Some(invite_id) => {
+                    let invite = invites.load(deps.storage, invite_id)?;
+                    if invite.stake.amount < min_stake {
+                        Bound::inclusive((min_stake.u128(), 0))
+                    } else {
+                        Bound::exclusive((invite.stake.amount.u128(), invite_id))
+                    }
+                }
+                None => Bound::inclusive((min_stake.u128(), 0)),
+            };
+            invites.idx.stake.sub_prefix(denom).range(
+                deps.storage,
+                Some(start),
+                None,
+                Order::Ascending,
+            )
+        }
+        (None, Some(_)) => {
+            return Err(StdError::generic_err(
+                "min_stake can only be given together with a denom",
+            ))
         }
-        None => {}
+        (Some(denom), None) => invites.idx.denom.prefix(denom).range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        ),
+        (None, None) => invites.range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        ),
+    };
+
+    records
+        .take(limit)
+        .map(|record| record.map(|(invite_id, invite)| OpenInviteResponse { invite_id, invite }))
+        .collect()
+}
+
+/**
+    Lists the games of a player in the given status, newest first
+    and older than start_after, using the host and opponent status indexes.
+*/
+fn player_games_by_status<'a>(
+    deps: Deps<'a>,
+    player: &Addr,
+    role: Option<Role>,
+    status: Status,
+    start_after: Option<u64>,
+) -> Box<dyn Iterator<Item = GameRecord> + 'a> {
+    let games = games();
+    let key = (player.clone(), status.to_string());
+    let end = start_after.map(Bound::exclusive);
+    let hosted = games.idx.host_status.prefix(key.clone()).range(
+        deps.storage,
+        None,
+        end.clone(),
+        Order::Descending,
+    );
+    let invited =
+        games
+            .idx
+            .opponent_status
+            .prefix(key)
+            .range(deps.storage, None, end, Order::Descending);
+
+    match role {
+        Some(Role::Host) => hosted,
+        Some(Role::Opponent) => invited,
+        None => Box::new(merge_by_game_id(hosted, invited, Order::Descending)),
     }
-    Ok(res)
+}
+
+/**
+    Merges two iterators of games sorted by game id
+    in the given order into a single one that keeps it.
+*/
+fn merge_by_game_id<'a>(
+    left: impl Iterator<Item = GameRecord> + 'a,
+    right: impl Iterator<Item = GameRecord> + 'a,
+    order: Order,
+) -> impl Iterator<Item = GameRecord> + 'a {
+    let descending = matches!(order, Order::Descending);
+    let mut left = left.peekable();
+    let mut right = right.peekable();
+
+    std::iter::from_fn(move || match (left.peek(), right.peek()) {
+        (Some(Ok((left_id, _))), Some(Ok((right_id, _))))
+            if (left_id > right_id) != descending && left_id != right_id =>
+        {
+            right.next()
+        }
+        (Some(_), _) => left.next(),
+        (None, _) => right.next(),
+    })
 }
@@ -1,12 +1,26 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::slice;
 
-use cosmwasm_std::{Addr, Coin, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Coin, Timestamp, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Game {
+    /**
+        Address of the player that created the game
+        through the invite message.
+    */
+    pub host: Addr,
+
+    /**
+        Address of the player invited to the game.
+    */
+    pub opponent: Addr,
+
     /**
         Board is defined as following due the
         smart contract optimization process:
@@ -21,6 +35,104 @@ pub struct Game {
     */
     pub board: Vec<Vec<Option<PlayerSymbol>>>,
 
+    /**
+        Rules chosen by the host when inviting the opponent.
+        Games stored before rulesets existed default to
+        the classic 3x3 board with 3 in a row.
+    */
+    #[serde(default)]
+    pub ruleset: Ruleset,
+
+    /**
+        Only used by the ultimate variant, keeping the winner
+        of each sub-board, where the sub-board on column x
+        and row y is stored as meta_board[y][x], and by the
+        notakto variant, keeping the player that killed each
+        board on meta_board[0][z].
+    */
+    #[serde(default)]
+    pub meta_board: Vec<Vec<Option<PlayerSymbol>>>,
+
+    /**
+        Only used by the ultimate variant. Sub-board where the
+        next move must be played, determined by the cell of the
+        previous move. None when any open sub-board can be chosen.
+    */
+    #[serde(default)]
+    pub forced_board: Option<Coord>,
+
+    /**
+        Player chosen by the host to move first with X.
+        Games stored before it existed were started by the host.
+    */
+    #[serde(default)]
+    pub first_player: FirstPlayer,
+
+    /**
+        Commit-reveal coin flip deciding the first player
+        of random games when the host commits to a secret.
+    */
+    #[serde(default)]
+    pub coin_flip: Option<CoinFlip>,
+
+    /**
+        Game this one is a rematch of, if any.
+    */
+    #[serde(default)]
+    pub previous_game: Option<u64>,
+
+    /**
+        Series the game belongs to, whose stake replaces
+        the prize of the game.
+    */
+    #[serde(default)]
+    pub series: Option<u64>,
+
+    /**
+        Tournament the game belongs to, whose entry
+        fees replace the prize of the game.
+    */
+    #[serde(default)]
+    pub tournament: Option<u64>,
+
+    /**
+        League the game belongs to, whose entry
+        fees replace the prize of the game.
+    */
+    #[serde(default)]
+    pub league: Option<u64>,
+
+    /**
+        Time the player on turn started waiting to move, only tracked
+        on series, tournament and league games, which anyone can
+        award to the other player once the move timeout passes.
+    */
+    #[serde(default)]
+    pub turn_started: Option<Timestamp>,
+
+    /**
+        Stake of the player on a game against the house, where
+        the prize also holds the winnings reserved from the
+        bankroll of the house.
+    */
+    #[serde(default)]
+    pub house_stake: Option<Coin>,
+
+    /**
+        CW20 token contract of the stake, whose amounts the
+        prize holds with the token address as denom. Native
+        coins are staked when it is not set.
+    */
+    #[serde(default)]
+    pub token: Option<Addr>,
+
+    /**
+        CW721 tokens escrowed as the stake of the game,
+        which replace the prize when it is set.
+    */
+    #[serde(default)]
+    pub nft_wager: Option<NftWager>,
+
     /**
         Used to determine the symbol of the player that
         started the game an the symbol of the opponent.
@@ -38,10 +150,24 @@ pub struct Game {
     */
     pub prize: Vec<Coin>,
 
+    /**
+        Coins each player put into the prize, so draws refund
+        them exactly. The stake of the host is empty on games
+        invited before they were tracked, and both are empty
+        on games also accepted before, whose prize is split
+        in halves.
+    */
+    #[serde(default)]
+    pub host_stake: Vec<Coin>,
+
+    #[serde(default)]
+    pub opponent_stake: Vec<Coin>,
+
     /**
         Determine the game status, where
         - INVITED: only one game can be in this status at a time per host and opponent pair. This status is achieved by creating a new game and the following possible status are PLAYING or REJECTED.
         - PLAYING: only one game can be in this status at a time per host and opponent pair. To achieve this status must mutate from INVITED.
+        - REVEALING: the opponent accepted a game decided by a coin flip and the host must reveal its secret. The following possible status are PLAYING or COMPLETED when the host does not reveal in time.
         - COMPLETED: multiple games can be in this status but they have to mutate from PLAYING.
         - REJECTED: multiple games can be in this status but they have to mutate from INVITE.
     */
@@ -64,6 +190,15 @@ pub enum PlayerSymbol {
     O,
 }
 
+impl PlayerSymbol {
+    pub fn opposite(self) -> PlayerSymbol {
+        match self {
+            PlayerSymbol::X => PlayerSymbol::O,
+            PlayerSymbol::O => PlayerSymbol::X,
+        }
+    }
+}
+
 impl fmt::Display for PlayerSymbol {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -77,6 +212,7 @@ impl fmt::Display for PlayerSymbol {
 pub enum Status {
     INVITED,
     PLAYING,
+    REVEALING,
     COMPLETED,
     REJECTED,
 }
@@ -86,49 +222,452 @@ impl fmt::Display for Status {
         match self {
             Status::INVITED => write!(f, "INVITED"),
             Status::PLAYING => write!(f, "PLAYING"),
+            Status::REVEALING => write!(f, "REVEALING"),
             Status::COMPLETED => write!(f, "COMPLETED"),
             Status::REJECTED => write!(f, "REJECTED"),
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /**
+        A single board won by the first player
+        with win_length symbols in a line.
+    */
+    #[default]
+    Classic,
+
+    /**
+        A board made of sub-boards, where the cell played decides
+        the sub-board of the next move and the game is won by
+        taking win_length sub-boards in a line.
+    */
+    Ultimate,
+
+    /**
+        A cube of board_size layers, known as Qubic when played
+        on 4x4x4, where lines can also cross the layers.
+    */
+    Qubic,
+
+    /**
+        Both players place X on one or more boards, a board
+        is dead once it contains win_length symbols in a line
+        and the player that kills the last live board loses.
+    */
+    Notakto,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FirstPlayer {
+    #[default]
+    Host,
+    Opponent,
+
+    /**
+        Decided by a coin flip the host commits to and reveals once
+        the opponent accepts, so the opponent cannot play on the
+        Accept message.
+    */
+    Random,
+}
+
+impl fmt::Display for FirstPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FirstPlayer::Host => write!(f, "host"),
+            FirstPlayer::Opponent => write!(f, "opponent"),
+            FirstPlayer::Random => write!(f, "random"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CoinFlip {
+    /**
+        SHA-256 hash of the secret of the host.
+    */
+    pub commitment: Binary,
+
+    /**
+        Value chosen by the opponent when accepting,
+        XORed with the secret to draw the first player.
+    */
+    pub nonce: Option<Binary>,
+
+    /**
+        Time after which the opponent can claim the prize
+        if the host has not revealed its secret.
+    */
+    pub reveal_deadline: Option<Timestamp>,
+}
+
+pub const COMMITMENT_LENGTH: usize = 32;
+pub const MIN_SECRET_LENGTH: usize = 16;
+
+impl CoinFlip {
+    pub fn new(commitment: Binary) -> CoinFlip {
+        CoinFlip {
+            commitment,
+            nonce: None,
+            reveal_deadline: None,
+        }
+    }
+
+    pub fn is_revealed_by(&self, secret: &Binary) -> bool {
+        secret.len() >= MIN_SECRET_LENGTH
+            && Sha256::digest(secret.as_slice())[..] == self.commitment[..]
+    }
+
+    /**
+        The host moves first when the XOR of every byte
+        of the secret and the nonce is even.
+    */
+    pub fn host_moves_first(&self, secret: &Binary) -> bool {
+        let nonce = self.nonce.clone().unwrap_or_default();
+        secret
+            .iter()
+            .chain(nonce.iter())
+            .fold(0u8, |result, byte| result ^ byte)
+            & 1
+            == 0
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Nft {
+    /**
+        CW721 contract of the collection the token belongs to.
+    */
+    pub collection: Addr,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftWager {
+    /**
+        Token escrowed by the host when sending the invite.
+    */
+    pub host_nft: Nft,
+
+    /**
+        Collections the opponent can escrow a token of
+        to accept the game.
+    */
+    pub allowed_collections: Vec<Addr>,
+
+    /**
+        Native coins the opponent can escrow instead of a
+        token, if the host is willing to play for them.
+    */
+    pub native_value: Option<Coin>,
+
+    /**
+        Token escrowed by the opponent when accepting.
+    */
+    pub opponent_nft: Option<Nft>,
+
+    /**
+        Coins escrowed by the opponent when accepting
+        with the native value.
+    */
+    pub opponent_funds: Vec<Coin>,
+}
+
+impl NftWager {
+    /**
+        Whether the opponent can accept the wager by escrowing
+        the given token, or the given funds without one.
+    */
+    pub fn accepts(&self, nft: Option<&Nft>, funds: &[Coin]) -> bool {
+        match (nft, &self.native_value) {
+            (Some(nft), _) => {
+                funds.is_empty() && self.allowed_collections.contains(&nft.collection)
+            }
+            (None, Some(native_value)) => funds == slice::from_ref(native_value),
+            (None, None) => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct Ruleset {
+    #[serde(default)]
+    pub variant: Variant,
+
+    /**
+        Amount of rows and columns of the board. On the
+        ultimate variant it is the size of each sub-board and
+        of the meta-board, so the board is board_size^2 wide.
+    */
+    #[serde(default = "Ruleset::default_board_size")]
+    pub board_size: u8,
+
+    /**
+        Amount of consecutive symbols in a row, column or
+        diagonal needed to win the game.
+    */
+    #[serde(default = "Ruleset::default_win_length")]
+    pub win_length: u8,
+
+    /**
+        Completing a line loses the game instead of winning it.
+    */
+    #[serde(default)]
+    pub misere: bool,
+
+    /**
+        Players choose to place X or O on every play message and
+        a line of either symbol is completed by the mover. The
+        moves of the invite and accept messages place the own
+        symbol of the player.
+    */
+    #[serde(default)]
+    pub wild: bool,
+
+    /**
+        Amount of boards of the notakto variant, which must
+        be 1 on every other variant.
+    */
+    #[serde(default = "Ruleset::default_boards")]
+    pub boards: u8,
+}
+
+pub const MIN_BOARD_SIZE: u8 = 3;
+pub const MAX_BOARD_SIZE: u8 = 19;
+pub const MAX_CUBE_SIZE: u8 = 4;
+pub const MAX_NOTAKTO_BOARDS: u8 = 9;
+
+impl Ruleset {
+    fn default_board_size() -> u8 {
+        3
+    }
+
+    fn default_win_length() -> u8 {
+        3
+    }
+
+    fn default_boards() -> u8 {
+        1
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let boards_are_valid = match self.variant {
+            Variant::Notakto => (1..=MAX_NOTAKTO_BOARDS).contains(&self.boards) && !self.wild,
+            Variant::Classic | Variant::Ultimate | Variant::Qubic => self.boards == 1,
+        };
+
+        boards_are_valid
+            && self.board_size >= MIN_BOARD_SIZE
+            && (MIN_BOARD_SIZE..=self.max_board_size()).contains(&self.board_width())
+            && (MIN_BOARD_SIZE..=self.board_size).contains(&self.win_length)
+    }
+
+    pub fn max_board_size(&self) -> u8 {
+        match self.variant {
+            Variant::Qubic => MAX_CUBE_SIZE,
+            Variant::Classic | Variant::Ultimate | Variant::Notakto => MAX_BOARD_SIZE,
+        }
+    }
+
+    /**
+        Amount of columns of the whole board.
+    */
+    pub fn board_width(&self) -> u8 {
+        match self.variant {
+            Variant::Classic | Variant::Qubic | Variant::Notakto => self.board_size,
+            Variant::Ultimate => self.board_size.saturating_mul(self.board_size),
+        }
+    }
+
+    /**
+        Amount of rows of the whole board. The layers of the
+        qubic variant and the boards of the notakto variant
+        are stacked one after the other.
+    */
+    pub fn board_height(&self) -> u8 {
+        self.board_width().saturating_mul(self.layers())
+    }
+
+    /**
+        Amount of layers addressed by the z coordinate.
+    */
+    pub fn layers(&self) -> u8 {
+        match self.variant {
+            Variant::Classic | Variant::Ultimate => 1,
+            Variant::Qubic => self.board_size,
+            Variant::Notakto => self.boards,
+        }
+    }
+
+    pub fn empty_board(&self) -> Vec<Vec<Option<PlayerSymbol>>> {
+        vec![vec![None; self.board_width() as usize]; self.board_height() as usize]
+    }
+
+    /**
+        Only qubic and notakto games accept, and require,
+        the z coordinate.
+    */
+    pub fn is_valid_coord(&self, coord: Coord) -> bool {
+        let width = self.board_width();
+        let layer_is_valid = match (self.variant, coord.z) {
+            (Variant::Qubic | Variant::Notakto, Some(z)) => z < self.layers(),
+            (Variant::Qubic | Variant::Notakto, None) | (_, Some(_)) => false,
+            (_, None) => true,
+        };
+
+        layer_is_valid && coord.x < width && coord.y < width
+    }
+
+    /**
+        Position of the coordinate on the board as (row, column).
+    */
+    pub fn cell_of(&self, coord: Coord) -> (usize, usize) {
+        let layer = coord.z.unwrap_or(0) as usize;
+        (
+            layer * self.board_size as usize + coord.y as usize,
+            coord.x as usize,
+        )
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            variant: Variant::default(),
+            board_size: Ruleset::default_board_size(),
+            win_length: Ruleset::default_win_length(),
+            misere: false,
+            wild: false,
+            boards: Ruleset::default_boards(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
 pub struct Coord {
     pub x: u8,
     pub y: u8,
+
+    /**
+        Layer of the move on the qubic variant, or board of the
+        move on the notakto variant, so 2D coordinates of the
+        other variants keep their original shape.
+    */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z: Option<u8>,
 }
 
-impl Coord {
-    pub fn is_valid(self) -> bool {
-        self.x < 3 && self.y < 3
-    }
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct Move {
+    pub player: PlayerSymbol,
+    pub coord: Coord,
+
+    /**
+        Symbol placed on wild and notakto games when
+        it is not the own symbol of the player.
+    */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<PlayerSymbol>,
+
+    pub height: u64,
+    pub time: Timestamp,
 }
 
 impl Game {
-    pub fn new(coord: Coord, prize: Vec<Coin>) -> Game {
-        let symbol_round = PlayerSymbol::O;
-        let mut board = vec![vec![None; 3]; 3];
-        let row = board.get_mut(coord.y as usize).unwrap();
-        row[coord.x as usize] = Some(PlayerSymbol::X);
-
-        Game {
-            board,
-            host_symbol: PlayerSymbol::X,
-            player_round: Some(symbol_round),
+    pub fn new(
+        host: Addr,
+        opponent: Addr,
+        ruleset: Ruleset,
+        first_player: FirstPlayer,
+        coord: Option<Coord>,
+        prize: Vec<Coin>,
+    ) -> Game {
+        let meta_board = match ruleset.variant {
+            Variant::Classic | Variant::Qubic => vec![],
+            Variant::Ultimate => {
+                vec![vec![None; ruleset.board_size as usize]; ruleset.board_size as usize]
+            }
+            Variant::Notakto => vec![vec![None; ruleset.boards as usize]],
+        };
+
+        let mut game = Game {
+            host,
+            opponent,
+            board: ruleset.empty_board(),
+            ruleset,
+            meta_board,
+            forced_board: None,
+            first_player,
+            coin_flip: None,
+            previous_game: None,
+            series: None,
+            tournament: None,
+            league: None,
+            turn_started: None,
+            house_stake: None,
+            token: None,
+            nft_wager: None,
+            host_symbol: match first_player {
+                FirstPlayer::Host | FirstPlayer::Random => PlayerSymbol::X,
+                FirstPlayer::Opponent => PlayerSymbol::O,
+            },
+            player_round: Some(PlayerSymbol::X),
+            host_stake: prize.clone(),
+            opponent_stake: vec![],
             prize,
             status: Status::INVITED,
             winner: None,
+        };
+        if let Some(coord) = coord {
+            game.play(coord).finish_round();
+        }
+
+        game
+    }
+
+    pub fn is_between(&self, host: &Addr, opponent: &Addr) -> bool {
+        self.host == *host && self.opponent == *opponent
+    }
+
+    pub fn symbol_of(&self, player: &Addr) -> Option<PlayerSymbol> {
+        if *player == self.host {
+            Some(self.host_symbol)
+        } else if *player == self.opponent {
+            Some(self.host_symbol.opposite())
+        } else {
+            None
+        }
+    }
+
+    pub fn player_of(&self, symbol: PlayerSymbol) -> &Addr {
+        if symbol == self.host_symbol {
+            &self.host
+        } else {
+            &self.opponent
+        }
+    }
+
+    /**
+        Whether the game waits for the given player, either to
+        play a move or, as host of a coin flip, to reveal.
+    */
+    pub fn is_turn_of(&self, player: &Addr) -> bool {
+        match self.status {
+            Status::PLAYING => {
+                self.player_round.is_some() && self.player_round == self.symbol_of(player)
+            }
+            Status::REVEALING => *player == self.host,
+            Status::INVITED | Status::COMPLETED | Status::REJECTED => false,
         }
     }
 
     pub fn already_played_on(&self, coord: Coord) -> bool {
-        return self
-            .board
-            .get(coord.y as usize)
-            .unwrap()
-            .get(coord.x as usize)
-            .unwrap()
-            .is_some();
+        let (row, column) = self.ruleset.cell_of(coord);
+        return self.board.get(row).unwrap().get(column).unwrap().is_some();
     }
 
     pub fn already_played(&mut self, as_host: bool) -> bool {
@@ -144,14 +683,53 @@ impl Game {
         }
     }
 
-    pub fn double_prize(&mut self) -> &mut Game {
-        for coin in &mut self.prize {
-            coin.amount = coin.amount.checked_mul(Uint128::new(2)).unwrap();
-        }
+    pub fn add_opponent_stake(&mut self, stake: Vec<Coin>) -> &mut Game {
+        add_coins(&mut self.prize, &stake);
+        self.prize = normalize_coins(&self.prize);
+        self.opponent_stake = stake;
 
         self
     }
 
+    pub fn stake_of(&self, player: &Addr) -> Vec<Coin> {
+        let (host_stake, opponent_stake) = self.draw_refunds();
+        if *player == self.host {
+            host_stake
+        } else {
+            opponent_stake
+        }
+    }
+
+    /**
+        Coins refunded to the host and the opponent on a draw.
+        Without a tracked stake the opponent gets half of the
+        prize, and the host gets what the opponent did not put
+        in, so the odd coins go to the host and no dust is left.
+    */
+    pub fn draw_refunds(&self) -> (Vec<Coin>, Vec<Coin>) {
+        let opponent_refund = if self.host_stake.is_empty() && self.opponent_stake.is_empty() {
+            self.get_half_prize()
+        } else {
+            self.opponent_stake.clone()
+        };
+        if !self.host_stake.is_empty() {
+            return (self.host_stake.clone(), opponent_refund);
+        }
+
+        let host_refund = self
+            .prize
+            .iter()
+            .map(|coin| Coin {
+                denom: coin.denom.clone(),
+                amount: opponent_refund
+                    .iter()
+                    .find(|half| half.denom == coin.denom)
+                    .map_or(coin.amount, |half| coin.amount - half.amount),
+            })
+            .collect();
+        (host_refund, opponent_refund)
+    }
+
     pub fn get_half_prize(&self) -> Vec<Coin> {
         self.prize
             .clone()
@@ -163,9 +741,81 @@ impl Game {
             .collect()
     }
 
+    /**
+        Checks the variant specific restrictions of a move. On the
+        ultimate variant the move must be played on the forced
+        sub-board, or on any open one when there is none. On the
+        notakto variant the move must be played on a live board.
+    */
+    pub fn is_playable(&self, coord: Coord) -> bool {
+        match self.ruleset.variant {
+            Variant::Classic | Variant::Qubic => true,
+            Variant::Notakto => self.meta_board[0][coord.z.unwrap_or(0) as usize].is_none(),
+            Variant::Ultimate => {
+                let sub_board = self.sub_board_of(coord);
+                self.is_sub_board_open(sub_board)
+                    && self.forced_board.unwrap_or(sub_board) == sub_board
+            }
+        }
+    }
+
     pub fn play(&mut self, coord: Coord) -> &mut Game {
-        let row = self.board.get_mut(coord.y as usize).unwrap();
-        row[coord.x as usize] = self.player_round;
+        let symbol = self.default_symbol();
+        self.play_symbol(coord, symbol)
+    }
+
+    /**
+        Symbol placed by the current player when none is chosen,
+        which is always X on the notakto variant.
+    */
+    pub fn default_symbol(&self) -> PlayerSymbol {
+        match self.ruleset.variant {
+            Variant::Notakto => PlayerSymbol::X,
+            Variant::Classic | Variant::Ultimate | Variant::Qubic => self.player_round.unwrap(),
+        }
+    }
+
+    /**
+        Places the given symbol for the current player,
+        which only differs from its own on wild games.
+    */
+    pub fn play_symbol(&mut self, coord: Coord, symbol: PlayerSymbol) -> &mut Game {
+        let (row, column) = self.ruleset.cell_of(coord);
+        self.board.get_mut(row).unwrap()[column] = Some(symbol);
+
+        if self.ruleset.variant == Variant::Ultimate {
+            let size = self.ruleset.board_size;
+            let sub_board = self.sub_board_of(coord);
+            let (offset_x, offset_y) = (sub_board.x * size, sub_board.y * size);
+            let sub_board_won = completes_line(
+                |x, y, _| {
+                    self.board[(offset_y as i32 + y) as usize][(offset_x as i32 + x) as usize]
+                },
+                size,
+                Coord {
+                    x: coord.x - offset_x,
+                    y: coord.y - offset_y,
+                    z: None,
+                },
+                Some(symbol),
+                self.ruleset.win_length,
+                &PLANE_DIRECTIONS,
+            );
+            if sub_board_won {
+                self.meta_board[sub_board.y as usize][sub_board.x as usize] = self.player_round;
+            }
+
+            let next_board = Coord {
+                x: coord.x % size,
+                y: coord.y % size,
+                z: None,
+            };
+            self.forced_board = Some(next_board).filter(|next| self.is_sub_board_open(*next));
+        }
+
+        if self.ruleset.variant == Variant::Notakto && self.completes_line_on(coord) {
+            self.meta_board[0][coord.z.unwrap_or(0) as usize] = self.player_round;
+        }
 
         self
     }
@@ -181,6 +831,16 @@ impl Game {
     }
 
     pub fn is_full_board(&self) -> bool {
+        if self.ruleset.variant == Variant::Notakto {
+            return self.meta_board[0].iter().all(Option::is_some);
+        }
+        if self.ruleset.variant == Variant::Ultimate {
+            let size = self.ruleset.board_size;
+            return (0..size)
+                .flat_map(|y| (0..size).map(move |x| Coord { x, y, z: None }))
+                .all(|sub_board| !self.is_sub_board_open(sub_board));
+        }
+
         for row in &self.board {
             for cell in row {
                 if cell.is_none() {
@@ -191,40 +851,1204 @@ impl Game {
         true
     }
 
-    pub fn is_current_player_winner(&self) -> bool {
-        macro_rules! has {
-            ($y:expr, $x:expr) => {
-                self.board[$y][$x] == self.player_round
-            };
+    /**
+        Completes the game when the move played on the given
+        coordinate decides it, otherwise passes the turn. The
+        notakto variant is decided once every board is dead,
+        where the player that killed the last one loses.
+    */
+    pub fn resolve_move(&mut self, coord: Coord) -> &mut Game {
+        let is_notakto = self.ruleset.variant == Variant::Notakto;
+        let decided = if is_notakto {
+            self.is_full_board()
+        } else {
+            self.completes_line_on(coord)
+        };
+
+        if decided {
+            let mover = self.player_round.unwrap();
+            self.status = Status::COMPLETED;
+            self.winner = Some(if self.ruleset.misere != is_notakto {
+                mover.opposite()
+            } else {
+                mover
+            });
+            self.player_round = None;
+        } else if self.is_full_board() {
+            self.status = Status::COMPLETED;
+            self.player_round = None;
+        } else {
+            self.finish_round();
         }
 
-        // Horizontally
-        for row in 0..3 {
-            if has!(row, 0) && has!(row, 1) && has!(row, 2) {
-                return true;
+        self
+    }
+
+    /**
+        Checks whether the move played on the given coordinate
+        completes a line of `win_length` equal symbols,
+        horizontally, vertically or diagonally. On the ultimate
+        variant the line must be made of sub-boards won by the
+        current player and on the qubic variant it can also
+        cross the layers.
+    */
+    pub fn completes_line_on(&self, coord: Coord) -> bool {
+        let (row, column) = self.ruleset.cell_of(coord);
+        let symbol = self.board[row][column];
+
+        match self.ruleset.variant {
+            Variant::Classic => completes_line(
+                |x, y, _| self.board[y as usize][x as usize],
+                self.ruleset.board_size,
+                coord,
+                symbol,
+                self.ruleset.win_length,
+                &PLANE_DIRECTIONS,
+            ),
+            Variant::Notakto => {
+                let size = self.ruleset.board_size as i32;
+                let offset = coord.z.unwrap_or(0) as i32 * size;
+                completes_line(
+                    |x, y, _| self.board[(offset + y) as usize][x as usize],
+                    self.ruleset.board_size,
+                    Coord { z: None, ..coord },
+                    symbol,
+                    self.ruleset.win_length,
+                    &PLANE_DIRECTIONS,
+                )
+            }
+            Variant::Qubic => completes_line(
+                |x, y, z| self.board[(z * self.ruleset.board_size as i32 + y) as usize][x as usize],
+                self.ruleset.board_size,
+                coord,
+                symbol,
+                self.ruleset.win_length,
+                &SPACE_DIRECTIONS,
+            ),
+            Variant::Ultimate => {
+                let sub_board = self.sub_board_of(coord);
+                self.meta_board[sub_board.y as usize][sub_board.x as usize] == self.player_round
+                    && completes_line(
+                        |x, y, _| self.meta_board[y as usize][x as usize],
+                        self.ruleset.board_size,
+                        sub_board,
+                        self.player_round,
+                        self.ruleset.win_length,
+                        &PLANE_DIRECTIONS,
+                    )
             }
         }
+    }
 
-        // Vertically
-        for col in 0..3 {
-            if has!(0, col) && has!(1, col) && has!(2, col) {
-                return true;
-            }
+    fn sub_board_of(&self, coord: Coord) -> Coord {
+        Coord {
+            x: coord.x / self.ruleset.board_size,
+            y: coord.y / self.ruleset.board_size,
+            z: None,
         }
+    }
+
+    /**
+        A sub-board is open while nobody has won it
+        and it still has cells to play on.
+    */
+    fn is_sub_board_open(&self, sub_board: Coord) -> bool {
+        let size = self.ruleset.board_size as usize;
+        let (offset_x, offset_y) = (sub_board.x as usize * size, sub_board.y as usize * size);
 
-        // Top-left to bottom-right
-        if has!(0, 0) && has!(1, 1) && has!(2, 2) {
+        self.meta_board[sub_board.y as usize][sub_board.x as usize].is_none()
+            && self.board[offset_y..offset_y + size]
+                .iter()
+                .any(|row| row[offset_x..offset_x + size].iter().any(Option::is_none))
+    }
+}
+
+/**
+    Directions of the lines on a single board:
+    rows, columns and both diagonals.
+*/
+const PLANE_DIRECTIONS: [(i32, i32, i32); 4] = [(1, 0, 0), (0, 1, 0), (1, 1, 0), (1, -1, 0)];
+
+/**
+    Directions of the lines on a cube, which on 4x4x4
+    add up to the 76 winning lines of Qubic.
+*/
+const SPACE_DIRECTIONS: [(i32, i32, i32); 13] = [
+    (1, 0, 0),
+    (0, 1, 0),
+    (0, 0, 1),
+    (1, 1, 0),
+    (1, -1, 0),
+    (1, 0, 1),
+    (1, 0, -1),
+    (0, 1, 1),
+    (0, 1, -1),
+    (1, 1, 1),
+    (1, 1, -1),
+    (1, -1, 1),
+    (1, -1, -1),
+];
+
+/**
+    Counts the consecutive cells holding `symbol` through `coord`
+    on a board of the given size, along each of the directions.
+*/
+fn completes_line(
+    cell: impl Fn(i32, i32, i32) -> Option<PlayerSymbol>,
+    size: u8,
+    coord: Coord,
+    symbol: Option<PlayerSymbol>,
+    win_length: u8,
+    directions: &[(i32, i32, i32)],
+) -> bool {
+    let size = size as i32;
+    let inside = |value: i32| value >= 0 && value < size;
+    let has =
+        |x: i32, y: i32, z: i32| inside(x) && inside(y) && inside(z) && cell(x, y, z) == symbol;
+
+    for (dx, dy, dz) in directions {
+        let mut line = 1;
+        for direction in [1, -1] {
+            let (mut x, mut y, mut z) =
+                (coord.x as i32, coord.y as i32, coord.z.unwrap_or(0) as i32);
+            loop {
+                x += dx * direction;
+                y += dy * direction;
+                z += dz * direction;
+                if !has(x, y, z) {
+                    break;
+                }
+                line += 1;
+            }
+        }
+        if line >= win_length {
             return true;
         }
+    }
 
-        // Top-right to bottom-left
-        if has!(0, 2) && has!(1, 1) && has!(2, 0) {
-            return true;
+    false
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Series {
+    pub host: Addr,
+    pub opponent: Addr,
+
+    /**
+        Maximum amount of games of the series, which is won
+        by the first player winning more than half of them.
+    */
+    pub best_of: u8,
+
+    /**
+        Rules of every game of the series.
+    */
+    pub ruleset: Ruleset,
+
+    /**
+        Seconds a player has to move on a game of the
+        series before it can be awarded to the other.
+    */
+    pub move_timeout: u64,
+
+    /**
+        Stake of both players, paid once for the whole series.
+    */
+    pub prize: Vec<Coin>,
+
+    pub host_wins: u8,
+    pub opponent_wins: u8,
+    pub draws: u8,
+
+    /**
+        Games of the series in the order they were played,
+        where the host moves first on the even ones.
+    */
+    pub game_ids: Vec<u64>,
+
+    pub status: Status,
+
+    /**
+        Player that won the series. None on a completed
+        series means that it ended in a draw.
+    */
+    pub winner: Option<Addr>,
+}
+
+pub const MAX_SERIES_GAMES: u8 = 15;
+
+impl Series {
+    pub fn is_valid_best_of(best_of: u8) -> bool {
+        best_of % 2 == 1 && best_of <= MAX_SERIES_GAMES
+    }
+
+    pub fn games_played(&self) -> u8 {
+        self.host_wins + self.opponent_wins + self.draws
+    }
+
+    /**
+        Adds the result of a completed game to the score.
+    */
+    pub fn score(&mut self, game: &Game) {
+        match game.winner.map(|winner| game.player_of(winner)) {
+            Some(winner) if *winner == self.host => self.host_wins += 1,
+            Some(_) => self.opponent_wins += 1,
+            None => self.draws += 1,
         }
+    }
+
+    /**
+        A series ends when a player wins the majority of the
+        games or once every game has been played.
+    */
+    pub fn is_decided(&self) -> bool {
+        self.host_wins * 2 > self.best_of
+            || self.opponent_wins * 2 > self.best_of
+            || self.games_played() >= self.best_of
+    }
+
+    pub fn leader(&self) -> Option<&Addr> {
+        match self.host_wins.cmp(&self.opponent_wins) {
+            Ordering::Greater => Some(&self.host),
+            Ordering::Less => Some(&self.opponent),
+            Ordering::Equal => None,
+        }
+    }
+
+    pub fn next_first_player(&self) -> FirstPlayer {
+        if self.game_ids.len() % 2 == 1 {
+            FirstPlayer::Opponent
+        } else {
+            FirstPlayer::Host
+        }
+    }
+
+    pub fn double_prize(&mut self) -> &mut Series {
+        for coin in &mut self.prize {
+            coin.amount = coin.amount.checked_mul(Uint128::new(2)).unwrap();
+        }
+
+        self
+    }
 
-        false
+    pub fn get_half_prize(&self) -> Vec<Coin> {
+        self.prize
+            .iter()
+            .map(|coin| Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount.checked_div(Uint128::new(2)).unwrap(),
+            })
+            .collect()
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /**
+        Address allowed to update the configuration,
+        set to the instantiator of the contract.
+    */
+    pub admin: Addr,
+
+    /**
+        Maximum amount of INVITED or PLAYING games that
+        can exist at the same time between two players,
+        no matter who hosts them.
+    */
+    pub max_open_games_per_pair: u32,
+
+    /**
+        Seconds the host has to reveal its secret on
+        coin flip games before forfeiting the prize.
+    */
+    #[serde(default = "Config::default_reveal_timeout")]
+    pub reveal_timeout: u64,
+
+    #[serde(default)]
+    pub house: HouseConfig,
+
+    /**
+        Address the collected fees are withdrawn to,
+        the admin receiving them when it is not set.
+    */
+    #[serde(default)]
+    pub treasury: Option<Addr>,
+
+    /**
+        Platform fee deducted from the prize of completed
+        games when paying it out, in basis points.
+    */
+    #[serde(default)]
+    pub fee_bps: u32,
+
+    /**
+        Denoms players can stake on games, along with their
+        limits. Any denom can be staked when it is empty.
+    */
+    #[serde(default)]
+    pub stake_limits: Vec<StakeLimit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeLimit {
+    pub denom: String,
+    pub min: Uint128,
+    pub max: Option<Uint128>,
+}
+
+impl Config {
+    fn default_reveal_timeout() -> u64 {
+        DEFAULT_REVEAL_TIMEOUT
+    }
+
+    pub fn treasury(&self) -> &Addr {
+        self.treasury.as_ref().unwrap_or(&self.admin)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HouseDifficulty {
+    /**
+        The house plays a random empty cell.
+    */
+    Random,
+
+    /**
+        The house plays a perfect game, so
+        it can only be drawn or beaten.
+    */
+    #[default]
+    Optimal,
+}
+
+impl fmt::Display for HouseDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HouseDifficulty::Random => write!(f, "random"),
+            HouseDifficulty::Optimal => write!(f, "optimal"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct HouseConfig {
+    pub difficulty: HouseDifficulty,
+
+    /**
+        Winnings paid on top of the stake when the player
+        beats the house, in basis points of the stake.
+    */
+    pub payout_bps: u32,
+
+    /**
+        Maximum winnings a single game can reserve,
+        in basis points of the bankroll of its denom.
+    */
+    pub max_exposure_bps: u32,
+}
+
+impl Default for HouseConfig {
+    fn default() -> Self {
+        HouseConfig {
+            difficulty: HouseDifficulty::default(),
+            payout_bps: MAX_BPS,
+            max_exposure_bps: 1_000,
+        }
+    }
+}
+
+pub const MAX_BPS: u32 = 10_000;
+pub const DEFAULT_MAX_OPEN_GAMES_PER_PAIR: u32 = 1;
+pub const DEFAULT_REVEAL_TIMEOUT: u64 = 24 * 60 * 60;
+pub const DEFAULT_MOVE_TIMEOUT: u64 = 24 * 60 * 60;
+
+pub const CONFIG: Item<Config> = Item::new("tic-tac-toe-config");
 pub const GAMES_COUNT: Item<u64> = Item::new("tic-tac-toe-count");
-pub const GAMES: Map<(&Addr, &Addr, u64), Game> = Map::new("tic-tac-toe");
+
+/**
+    Funds of the house available to back new games,
+    keyed by denom. Winnings reserved by ongoing games
+    are not included.
+*/
+pub const HOUSE_BANKROLL: Map<&str, Uint128> = Map::new("tic-tac-toe-house-bankroll");
+
+/**
+    Platform fees collected on native prizes, keyed by
+    denom, until they are withdrawn to the treasury.
+*/
+pub const FEES: Map<&str, Uint128> = Map::new("tic-tac-toe-fees");
+
+/**
+    Platform fees collected on CW20 prizes, keyed by the
+    token contract, until they are withdrawn to the treasury.
+*/
+pub const TOKEN_FEES: Map<&Addr, Uint128> = Map::new("tic-tac-toe-token-fees");
+
+/**
+    Ids of the INVITED and PLAYING games between two players,
+    keyed by the unordered pair returned from `pair_key`.
+*/
+pub const OPEN_GAMES: Map<(&Addr, &Addr), Vec<u64>> = Map::new("tic-tac-toe-open");
+
+/**
+    Append-only history of the moves of each game,
+    keyed by game id and the index of the move.
+*/
+pub const MOVES: Map<(u64, u32), Move> = Map::new("tic-tac-toe-moves");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OpenInvite {
+    pub host: Addr,
+
+    /**
+        Stake of the host, which the player
+        accepting the invite must match.
+    */
+    pub stake: Coin,
+
+    pub ruleset: Ruleset,
+    pub first_player: FirstPlayer,
+
+    /**
+        Players allowed to accept the invite.
+        None when anyone can accept it.
+    */
+    pub allow_list: Option<Vec<Addr>>,
+}
+
+impl OpenInvite {
+    pub fn allows(&self, player: &Addr) -> bool {
+        *player != self.host
+            && match &self.allow_list {
+                Some(allow_list) => allow_list.contains(player),
+                None => true,
+            }
+    }
+
+    /**
+        Whether auto match can pair players with the invite,
+        which takes only public invites of the default ruleset.
+    */
+    pub fn is_matchable(&self) -> bool {
+        self.allow_list.is_none() && self.ruleset == Ruleset::default()
+    }
+}
+
+pub const OPEN_INVITES_COUNT: Item<u64> = Item::new("tic-tac-toe-lobby-count");
+
+pub struct OpenInviteIndexes<'a> {
+    pub denom: MultiIndex<'a, String, OpenInvite, u64>,
+    pub stake: MultiIndex<'a, (String, u128), OpenInvite, u64>,
+
+    /**
+        Stake of the invite together with 1 when it is matchable and 0
+        otherwise, so that auto match skips the invites it cannot take.
+    */
+    pub auto_match: MultiIndex<'a, (String, u128, u8), OpenInvite, u64>,
+}
+
+pub const MAX_AUTO_MATCH_SCAN: usize = 30;
+
+impl<'a> IndexList<OpenInvite> for OpenInviteIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<OpenInvite>> + '_> {
+        let v: Vec<&dyn Index<OpenInvite>> = vec![&self.denom, &self.stake, &self.auto_match];
+        Box::new(v.into_iter())
+    }
+}
+
+/**
+    Open invites waiting for an opponent, indexed
+    by the denom of their stake for the lobby.
+*/
+pub fn open_invites<'a>() -> IndexedMap<'a, u64, OpenInvite, OpenInviteIndexes<'a>> {
+    let indexes = OpenInviteIndexes {
+        denom: MultiIndex::new(
+            |invite: &OpenInvite| invite.stake.denom.clone(),
+            "tic-tac-toe-lobby",
+            "tic-tac-toe-lobby__denom",
+        ),
+        stake: MultiIndex::new(
+            |invite: &OpenInvite| (invite.stake.denom.clone(), invite.stake.amount.u128()),
+            "tic-tac-toe-lobby",
+            "tic-tac-toe-lobby__stake",
+        ),
+        auto_match: MultiIndex::new(
+            |invite: &OpenInvite| {
+                (
+                    invite.stake.denom.clone(),
+                    invite.stake.amount.u128(),
+                    u8::from(invite.is_matchable()),
+                )
+            },
+            "tic-tac-toe-lobby",
+            "tic-tac-toe-lobby__auto_match",
+        ),
+    };
+    IndexedMap::new("tic-tac-toe-lobby", indexes)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentMatch {
+    pub host: Addr,
+    pub opponent: Addr,
+
+    /**
+        Current game of the match, replaced by a rematch
+        with swapped first player when a game is drawn.
+    */
+    pub game_id: u64,
+
+    /**
+        Drawn games of the match so far. The host, who is the
+        better seed, wins the match on the last allowed draw.
+    */
+    pub draws: u8,
+
+    pub winner: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Tournament {
+    pub organizer: Addr,
+    pub entry_fee: Coin,
+    pub max_players: u32,
+
+    /**
+        Time from which anyone can start the tournament
+        before it is full.
+    */
+    pub start_time: Timestamp,
+
+    /**
+        Percentage of the prize pool paid to each final
+        placement, starting with the winner.
+    */
+    pub payout_split: Vec<u8>,
+
+    pub ruleset: Ruleset,
+
+    /**
+        Seconds a player has to move on a game of the
+        tournament before it can be awarded to the other.
+    */
+    pub move_timeout: u64,
+
+    /**
+        Players in the order they joined, which is
+        also their seed on the bracket.
+    */
+    pub players: Vec<Addr>,
+
+    pub round: u32,
+
+    /**
+        Players of the current round that got a bye and
+        play the next round without playing this one.
+    */
+    pub byes: Vec<Addr>,
+
+    pub matches: Vec<TournamentMatch>,
+
+    /**
+        Players in the order they were eliminated, so
+        the last one is the runner-up.
+    */
+    pub eliminated: Vec<Addr>,
+
+    /**
+        INVITED while players can join, PLAYING once the first
+        round starts, COMPLETED when the prizes are paid and
+        REJECTED when it started without enough players.
+    */
+    pub status: Status,
+
+    pub winner: Option<Addr>,
+}
+
+pub const MAX_TOURNAMENT_PLAYERS: u32 = 64;
+pub const MAX_TOURNAMENT_DRAWS: u8 = 3;
+
+impl Tournament {
+    pub fn prize_pool(&self) -> Coin {
+        Coin {
+            denom: self.entry_fee.denom.clone(),
+            amount: self.entry_fee.amount * Uint128::from(self.players.len() as u128),
+        }
+    }
+
+    /**
+        Final placements, from the winner to the
+        first player eliminated.
+    */
+    pub fn placements(&self) -> Vec<&Addr> {
+        self.winner
+            .iter()
+            .chain(self.eliminated.iter().rev())
+            .collect()
+    }
+
+    /**
+        Players that advanced to the next round so far,
+        in bracket order.
+    */
+    pub fn advancing(&self) -> Vec<Addr> {
+        self.byes
+            .iter()
+            .cloned()
+            .chain(self.matches.iter().filter_map(|game| game.winner.clone()))
+            .collect()
+    }
+
+    pub fn is_round_complete(&self) -> bool {
+        self.matches.iter().all(|game| game.winner.is_some())
+    }
+}
+
+pub const TOURNAMENTS_COUNT: Item<u64> = Item::new("tic-tac-toe-tournaments-count");
+pub const TOURNAMENTS: Map<u64, Tournament> = Map::new("tic-tac-toe-tournaments");
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct LeaguePoints {
+    pub win: u32,
+    pub draw: u32,
+    pub loss: u32,
+}
+
+impl Default for LeaguePoints {
+    fn default() -> Self {
+        LeaguePoints {
+            win: 3,
+            draw: 1,
+            loss: 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeagueStanding {
+    pub player: Addr,
+    pub points: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl LeagueStanding {
+    pub fn new(player: Addr) -> Self {
+        LeagueStanding {
+            player,
+            points: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+        }
+    }
+
+    /**
+        Ranks by points, then by wins and then by fewer
+        losses. Players still tied keep their join order.
+    */
+    pub fn rank(&self, other: &Self) -> Ordering {
+        other
+            .points
+            .cmp(&self.points)
+            .then(other.wins.cmp(&self.wins))
+            .then(self.losses.cmp(&other.losses))
+    }
+
+    /**
+        Awards the points of a completed game, where
+        won is None on draws.
+    */
+    pub fn record(&mut self, won: Option<bool>, points: &LeaguePoints) {
+        match won {
+            Some(true) => {
+                self.wins += 1;
+                self.points += points.win;
+            }
+            Some(false) => {
+                self.losses += 1;
+                self.points += points.loss;
+            }
+            None => {
+                self.draws += 1;
+                self.points += points.draw;
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct League {
+    pub organizer: Addr,
+    pub entry_fee: Coin,
+    pub max_players: u32,
+
+    /**
+        Time from which anyone can start the league
+        before it is full.
+    */
+    pub start_time: Timestamp,
+
+    /**
+        Whether every pair plays twice, each player
+        moving first in one of the games.
+    */
+    pub double_round_robin: bool,
+
+    pub points: LeaguePoints,
+
+    /**
+        Percentage of the prize pool paid to each final
+        placement, starting with the winner.
+    */
+    pub payout_split: Vec<u8>,
+
+    pub ruleset: Ruleset,
+
+    /**
+        Seconds a player has to move on a game of the
+        league before it can be awarded to the other.
+    */
+    pub move_timeout: u64,
+
+    /**
+        One entry per player, in the order they joined.
+    */
+    pub standings: Vec<LeagueStanding>,
+
+    /**
+        Scheduled games that are not completed yet.
+    */
+    pub games_left: u32,
+
+    /**
+        INVITED while players can join, PLAYING once the games
+        are scheduled, COMPLETED when the prizes are paid and
+        REJECTED when it started without enough players.
+    */
+    pub status: Status,
+
+    pub winner: Option<Addr>,
+}
+
+pub const MAX_LEAGUE_PLAYERS: u32 = 16;
+
+impl League {
+    pub fn prize_pool(&self) -> Coin {
+        Coin {
+            denom: self.entry_fee.denom.clone(),
+            amount: self.entry_fee.amount * Uint128::from(self.standings.len() as u128),
+        }
+    }
+
+    pub fn players(&self) -> Vec<Addr> {
+        self.standings
+            .iter()
+            .map(|standing| standing.player.clone())
+            .collect()
+    }
+
+    /**
+        Standings sorted from the first to the last place.
+    */
+    pub fn ranking(&self) -> Vec<LeagueStanding> {
+        let mut ranking = self.standings.clone();
+        ranking.sort_by(LeagueStanding::rank);
+        ranking
+    }
+
+    /**
+        Awards the points of a completed game of the league
+        to both of its players.
+    */
+    pub fn score(&mut self, game: &Game) {
+        let points = self.points;
+        for standing in self.standings.iter_mut() {
+            if let Some(symbol) = game.symbol_of(&standing.player) {
+                standing.record(game.winner.map(|winner| winner == symbol), &points);
+            }
+        }
+        self.games_left -= 1;
+    }
+}
+
+pub const LEAGUES_COUNT: Item<u64> = Item::new("tic-tac-toe-leagues-count");
+pub const LEAGUES: Map<u64, League> = Map::new("tic-tac-toe-leagues");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Season {
+    /**
+        Games completed from the start time and before
+        the end time score points for the season.
+    */
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+
+    /**
+        Funded by the admin when creating the season.
+    */
+    pub prize_pool: Coin,
+
+    /**
+        Percentage of the prize pool paid to each of
+        the top players, starting with the first one.
+    */
+    pub payout_split: Vec<u8>,
+
+    pub points: LeaguePoints,
+
+    /**
+        PLAYING until the season is finalized,
+        then COMPLETED.
+    */
+    pub status: Status,
+
+    /**
+        Players paid when the season was finalized,
+        from the first place.
+    */
+    pub winners: Vec<Addr>,
+}
+
+pub const MAX_SEASON_WINNERS: u32 = 10;
+
+impl Season {
+    pub fn is_active_at(&self, time: Timestamp) -> bool {
+        self.start_time <= time && time < self.end_time
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeasonScore {
+    pub season_id: u64,
+    pub standing: LeagueStanding,
+}
+
+pub const SEASONS_COUNT: Item<u64> = Item::new("tic-tac-toe-seasons-count");
+pub const SEASONS: Map<u64, Season> = Map::new("tic-tac-toe-seasons");
+
+pub struct SeasonScoreIndexes<'a> {
+    pub points: MultiIndex<'a, (u64, u32), SeasonScore, (u64, &'a Addr)>,
+}
+
+impl<'a> IndexList<SeasonScore> for SeasonScoreIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<SeasonScore>> + '_> {
+        let v: Vec<&dyn Index<SeasonScore>> = vec![&self.points];
+        Box::new(v.into_iter())
+    }
+}
+
+/**
+    Scores of the players on each season, indexed by
+    season and points for the season rankings.
+*/
+pub fn season_scores<'a>() -> IndexedMap<'a, (u64, &'a Addr), SeasonScore, SeasonScoreIndexes<'a>> {
+    let indexes = SeasonScoreIndexes {
+        points: MultiIndex::new(
+            |score: &SeasonScore| (score.season_id, score.standing.points),
+            "tic-tac-toe-season-scores",
+            "tic-tac-toe-season-scores__points",
+        ),
+    };
+    IndexedMap::new("tic-tac-toe-season-scores", indexes)
+}
+
+pub const SERIES_COUNT: Item<u64> = Item::new("tic-tac-toe-series-count");
+pub const SERIES: Map<u64, Series> = Map::new("tic-tac-toe-series");
+
+/**
+    Links each completed game to its rematch, so together
+    with Game::previous_game the whole history can be walked.
+*/
+pub const REMATCHES: Map<u64, u64> = Map::new("tic-tac-toe-rematches");
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PlayerStats {
+    /**
+        Completed games, counting every game of
+        series, tournaments and leagues.
+    */
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+
+    /**
+        Invites and series the player rejected,
+        either as host or as opponent.
+    */
+    pub rejections: u32,
+
+    /**
+        Stakes and entry fees the player paid into games
+        that started, with one coin per denom.
+    */
+    pub staked: Vec<Coin>,
+
+    /**
+        Prizes the player received for winning, with one
+        coin per denom. Refunds and draws are not included.
+    */
+    pub won: Vec<Coin>,
+
+    pub win_streak: u32,
+    pub longest_win_streak: u32,
+}
+
+impl PlayerStats {
+    /**
+        Counts a completed game, where won is None on draws.
+    */
+    pub fn record_game(&mut self, won: Option<bool>) {
+        self.games_played += 1;
+        match won {
+            Some(true) => {
+                self.wins += 1;
+                self.win_streak += 1;
+                self.longest_win_streak = self.longest_win_streak.max(self.win_streak);
+            }
+            Some(false) => {
+                self.losses += 1;
+                self.win_streak = 0;
+            }
+            None => {
+                self.draws += 1;
+                self.win_streak = 0;
+            }
+        }
+    }
+
+    pub fn add_staked(&mut self, coins: &[Coin]) {
+        add_coins(&mut self.staked, coins);
+    }
+
+    pub fn add_won(&mut self, coins: &[Coin]) {
+        add_coins(&mut self.won, coins);
+    }
+}
+
+fn add_coins(total: &mut Vec<Coin>, coins: &[Coin]) {
+    for coin in coins {
+        match total.iter_mut().find(|total| total.denom == coin.denom) {
+            Some(total) => total.amount += coin.amount,
+            None => total.push(coin.clone()),
+        }
+    }
+}
+
+/**
+    Merges the coins of the same denom and drops the empty
+    ones, sorted by denom so lists can be compared as is.
+*/
+pub fn normalize_coins(coins: &[Coin]) -> Vec<Coin> {
+    let mut normalized = vec![];
+    add_coins(&mut normalized, coins);
+    normalized.retain(|coin| !coin.amount.is_zero());
+    normalized.sort_by(|a, b| a.denom.cmp(&b.denom));
+    normalized
+}
+
+pub const PLAYER_STATS: Map<&Addr, PlayerStats> = Map::new("tic-tac-toe-player-stats");
+
+pub const INITIAL_RATING: u32 = 1200;
+pub const RATING_K_FACTOR: i64 = 32;
+const MAX_RATING_DIFFERENCE: i64 = 800;
+const RATING_DIFFERENCE_STEP: i64 = 50;
+
+/**
+    Expected score, in thousandths, of the lower rated player
+    for every RATING_DIFFERENCE_STEP points of difference, so
+    Elo ratings are updated without floating point numbers.
+*/
+const EXPECTED_SCORES: [i64; 17] = [
+    500, 429, 360, 297, 240, 192, 151, 118, 91, 70, 53, 40, 31, 23, 17, 13, 10,
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlayerRating {
+    pub rating: u32,
+
+    /**
+        Rated games the player completed.
+    */
+    pub games: u32,
+}
+
+impl Default for PlayerRating {
+    fn default() -> Self {
+        PlayerRating {
+            rating: INITIAL_RATING,
+            games: 0,
+        }
+    }
+}
+
+impl PlayerRating {
+    /**
+        Expected score against the opponent in thousandths,
+        interpolated between the steps of EXPECTED_SCORES.
+    */
+    pub fn expected_score(&self, opponent: &PlayerRating) -> i64 {
+        let difference = (opponent.rating as i64 - self.rating as i64)
+            .clamp(-MAX_RATING_DIFFERENCE, MAX_RATING_DIFFERENCE);
+        let step = (difference.abs() / RATING_DIFFERENCE_STEP) as usize;
+        let lower = EXPECTED_SCORES[step];
+        let upper = EXPECTED_SCORES[(step + 1).min(EXPECTED_SCORES.len() - 1)];
+        let expected = lower
+            - (lower - upper) * (difference.abs() % RATING_DIFFERENCE_STEP)
+                / RATING_DIFFERENCE_STEP;
+        if difference >= 0 {
+            expected
+        } else {
+            1000 - expected
+        }
+    }
+
+    /**
+        Applies the score in thousandths obtained against
+        the opponent: 1000 for a win, 500 for a draw and
+        0 for a loss.
+    */
+    pub fn update(&mut self, opponent: &PlayerRating, score: i64) {
+        let change = RATING_K_FACTOR * (score - self.expected_score(opponent)) / 1000;
+        self.rating = (self.rating as i64 + change).max(0) as u32;
+        self.games += 1;
+    }
+}
+
+pub struct RatingIndexes<'a> {
+    pub rating: MultiIndex<'a, u32, PlayerRating, &'a Addr>,
+}
+
+impl<'a> IndexList<PlayerRating> for RatingIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<PlayerRating>> + '_> {
+        let v: Vec<&dyn Index<PlayerRating>> = vec![&self.rating];
+        Box::new(v.into_iter())
+    }
+}
+
+/**
+    Ratings of the players that completed a game,
+    indexed by rating for the leaderboard.
+*/
+pub fn ratings<'a>() -> IndexedMap<'a, &'a Addr, PlayerRating, RatingIndexes<'a>> {
+    let indexes = RatingIndexes {
+        rating: MultiIndex::new(
+            |rating: &PlayerRating| rating.rating,
+            "tic-tac-toe-ratings",
+            "tic-tac-toe-ratings__rating",
+        ),
+    };
+    IndexedMap::new("tic-tac-toe-ratings", indexes)
+}
+
+pub fn pair_key<'a>(player: &'a Addr, other_player: &'a Addr) -> (&'a Addr, &'a Addr) {
+    if player <= other_player {
+        (player, other_player)
+    } else {
+        (other_player, player)
+    }
+}
+
+/**
+    Game as stored by version 0.1.0, before games knew their
+    players and only held the board, the prize and the result.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyGame {
+    pub board: Vec<Vec<Option<PlayerSymbol>>>,
+    pub host_symbol: PlayerSymbol,
+    pub player_round: Option<PlayerSymbol>,
+    pub prize: Vec<Coin>,
+    pub status: Status,
+    pub winner: Option<PlayerSymbol>,
+}
+
+impl LegacyGame {
+    /**
+        Classic game started by the host, whose stakes were not
+        tracked, so draws split the prize like before.
+    */
+    pub fn into_game(self, host: Addr, opponent: Addr) -> Game {
+        let mut game = Game::new(
+            host,
+            opponent,
+            Ruleset::default(),
+            FirstPlayer::Host,
+            None,
+            self.prize,
+        );
+        game.board = self.board;
+        game.host_symbol = self.host_symbol;
+        game.player_round = self.player_round;
+        game.status = self.status;
+        game.winner = self.winner;
+        game.host_stake = vec![];
+        game
+    }
+}
+
+/**
+    Games of version 0.1.0 keyed by host, opponent and game id.
+*/
+pub const LEGACY_GAMES: Map<(&Addr, &Addr, u64), LegacyGame> = Map::new("tic-tac-toe");
+
+pub struct GameIndexes<'a> {
+    pub status: MultiIndex<'a, String, Game, u64>,
+    pub host: MultiIndex<'a, Addr, Game, u64>,
+    pub opponent: MultiIndex<'a, Addr, Game, u64>,
+    pub host_status: MultiIndex<'a, (Addr, String), Game, u64>,
+    pub opponent_status: MultiIndex<'a, (Addr, String), Game, u64>,
+}
+
+impl<'a> IndexList<Game> for GameIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Game>> + '_> {
+        let v: Vec<&dyn Index<Game>> = vec![
+            &self.status,
+            &self.host,
+            &self.opponent,
+            &self.host_status,
+            &self.opponent_status,
+        ];
+        Box::new(v.into_iter())
+    }
+}
+
+/**
+    Games are stored by their id and indexed by status,
+    host and opponent so they can be listed without
+    ranging over the whole map. Games of version 0.1.0
+    are moved here from LEGACY_GAMES by the migration.
+*/
+pub fn games<'a>() -> IndexedMap<'a, u64, Game, GameIndexes<'a>> {
+    let indexes = GameIndexes {
+        status: MultiIndex::new(
+            |game: &Game| game.status.to_string(),
+            "tic-tac-toe-games",
+            "tic-tac-toe-games__status",
+        ),
+        host: MultiIndex::new(
+            |game: &Game| game.host.clone(),
+            "tic-tac-toe-games",
+            "tic-tac-toe-games__host",
+        ),
+        opponent: MultiIndex::new(
+            |game: &Game| game.opponent.clone(),
+            "tic-tac-toe-games",
+            "tic-tac-toe-games__opponent",
+        ),
+        host_status: MultiIndex::new(
+            |game: &Game| (game.host.clone(), game.status.to_string()),
+            "tic-tac-toe-games",
+            "tic-tac-toe-games__host_status",
+        ),
+        opponent_status: MultiIndex::new(
+            |game: &Game| (game.opponent.clone(), game.status.to_string()),
+            "tic-tac-toe-games",
+            "tic-tac-toe-games__opponent_status",
+        ),
+    };
+    IndexedMap::new("tic-tac-toe-games", indexes)
+}
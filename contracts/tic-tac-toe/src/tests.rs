@@ -1,15 +1,34 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{coins, from_binary, Addr, Response, StdError};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, MemoryStorage,
+    Order, OwnedDeps, Response, StdError, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use sha2::{Digest, Sha256};
 
 use crate::contract::execute;
 use crate::contract::instantiate;
+use crate::contract::migrate;
 use crate::contract::query;
-use crate::data::GameResponse;
+use crate::data::{
+    EscrowBalanceResponse, FeesResponse, GameResponse, OpenInviteResponse, PlayerGamesResponse,
+    PlayerStatsResponse, RatingResponse, ReplayResponse, SeasonRankingResponse, SeriesResponse,
+    StandingsResponse, TournamentResponse,
+};
 use crate::errors::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryKey, QueryMsg};
-use crate::state::{Coord, Game, PlayerSymbol, Status};
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, MigrateMsg, QueryKey, QueryMsg, ReceiveMsg, ReceiveNftMsg, Role,
+};
+use crate::state::{
+    Coord, FirstPlayer, Game, LegacyGame, Move, PlayerStats, PlayerSymbol, Ruleset, StakeLimit,
+    Status, Tournament, Variant, GAMES_COUNT, LEGACY_GAMES, MAX_AUTO_MATCH_SCAN,
+};
 
 const GAME_ID: u64 = 1;
+const SECRET: [u8; 16] = [7; 16];
 
 #[test]
 fn accept() {
@@ -21,7 +40,7 @@ fn accept() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
     execute(
@@ -29,8 +48,15 @@ fn accept() {
         mock_env(),
         host_info,
         ExecuteMsg::Invite {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
         },
     )
     .unwrap();
@@ -41,9 +67,14 @@ fn accept() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 1, y: 1 },
+            coord: Some(Coord {
+                x: 1,
+                y: 1,
+                z: None,
+            }),
             host: String::from("host"),
             game_id: GAME_ID,
+            nonce: None,
         },
     )
     .unwrap();
@@ -72,17 +103,35 @@ fn accept() {
     assert_eq!(
         query_value,
         vec![GameResponse {
+            game_id: GAME_ID,
             host: Addr::unchecked("host"),
             opponent: Addr::unchecked("opponent"),
             game: Game {
+                host: Addr::unchecked("host"),
+                opponent: Addr::unchecked("opponent"),
                 board: vec![
                     vec![None, None, Some(PlayerSymbol::X)],
                     vec![None, Some(PlayerSymbol::O), None],
                     vec![None, None, None]
                 ],
+                ruleset: Ruleset::default(),
+                meta_board: vec![],
+                forced_board: None,
+                first_player: FirstPlayer::Host,
+                coin_flip: None,
+                previous_game: None,
+                series: None,
+                tournament: None,
+                league: None,
+                turn_started: None,
+                house_stake: None,
+                token: None,
+                nft_wager: None,
                 player_round: Some(PlayerSymbol::X),
                 host_symbol: PlayerSymbol::X,
                 prize: coins(4, "token"),
+                host_stake: coins(2, "token"),
+                opponent_stake: coins(2, "token"),
                 status: Status::PLAYING,
                 winner: None
             }
@@ -100,7 +149,7 @@ fn accept_with_incorrect_host() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
     execute(
@@ -108,8 +157,15 @@ fn accept_with_incorrect_host() {
         mock_env(),
         host_info,
         ExecuteMsg::Invite {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
         },
     )
     .unwrap();
@@ -120,9 +176,14 @@ fn accept_with_incorrect_host() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 2, y: 2 },
+            coord: Some(Coord {
+                x: 2,
+                y: 2,
+                z: None,
+            }),
             host: String::from("w"),
             game_id: GAME_ID,
+            nonce: None,
         },
     );
 
@@ -146,7 +207,7 @@ fn accept_inexistent() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
 
@@ -157,9 +218,14 @@ fn accept_inexistent() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 1, y: 1 },
+            coord: Some(Coord {
+                x: 1,
+                y: 1,
+                z: None,
+            }),
             host: String::from("host"),
             game_id,
+            nonce: None,
         },
     );
 
@@ -184,7 +250,7 @@ fn accept_with_incorrect_coord() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
     execute(
@@ -192,8 +258,15 @@ fn accept_with_incorrect_coord() {
         mock_env(),
         host_info,
         ExecuteMsg::Invite {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
         },
     )
     .unwrap();
@@ -205,9 +278,14 @@ fn accept_with_incorrect_coord() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 5, y: 5 },
+            coord: Some(Coord {
+                x: 5,
+                y: 5,
+                z: None,
+            }),
             host: String::from("host"),
             game_id,
+            nonce: None,
         },
     );
 
@@ -216,7 +294,11 @@ fn accept_with_incorrect_coord() {
     assert_eq!(
         value,
         ContractError::InvalidCoord {
-            coord: Coord { x: 5, y: 5 }
+            coord: Coord {
+                x: 5,
+                y: 5,
+                z: None
+            }
         }
     );
 }
@@ -231,7 +313,7 @@ fn accept_on_already_played_coords() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
     execute(
@@ -239,8 +321,15 @@ fn accept_on_already_played_coords() {
         mock_env(),
         host_info,
         ExecuteMsg::Invite {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
         },
     )
     .unwrap();
@@ -252,9 +341,14 @@ fn accept_on_already_played_coords() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             host: String::from("host"),
             game_id,
+            nonce: None,
         },
     );
 
@@ -263,7 +357,11 @@ fn accept_on_already_played_coords() {
     assert_eq!(
         value,
         ContractError::CoordinateAlreadyPlayed {
-            coord: Coord { x: 2, y: 0 }
+            coord: Coord {
+                x: 2,
+                y: 0,
+                z: None
+            }
         }
     );
 }
@@ -278,7 +376,7 @@ fn accept_with_less_funds() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
     execute(
@@ -286,8 +384,15 @@ fn accept_with_less_funds() {
         mock_env(),
         host_info,
         ExecuteMsg::Invite {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
         },
     )
     .unwrap();
@@ -299,9 +404,14 @@ fn accept_with_less_funds() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 1, y: 2 },
+            coord: Some(Coord {
+                x: 1,
+                y: 2,
+                z: None,
+            }),
             host: String::from("host"),
             game_id,
+            nonce: None,
         },
     );
 
@@ -320,7 +430,7 @@ fn accept_with_more_funds() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
     execute(
@@ -328,8 +438,15 @@ fn accept_with_more_funds() {
         mock_env(),
         host_info,
         ExecuteMsg::Invite {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
         },
     )
     .unwrap();
@@ -341,9 +458,14 @@ fn accept_with_more_funds() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 1, y: 2 },
+            coord: Some(Coord {
+                x: 1,
+                y: 2,
+                z: None,
+            }),
             host: String::from("host"),
             game_id,
+            nonce: None,
         },
     );
 
@@ -362,7 +484,7 @@ fn accept_with_different_funds() {
         deps.as_mut(),
         mock_env(),
         host_info.clone(),
-        InstantiateMsg {},
+        InstantiateMsg::default(),
     )
     .unwrap();
     execute(
@@ -370,8 +492,15 @@ fn accept_with_different_funds() {
         mock_env(),
         host_info,
         ExecuteMsg::Invite {
-            coord: Coord { x: 2, y: 0 },
+            coord: Some(Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            }),
             opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
         },
     )
     .unwrap();
@@ -383,9 +512,14 @@ fn accept_with_different_funds() {
         mock_env(),
         opponent_info,
         ExecuteMsg::Accept {
-            coord: Coord { x: 1, y: 2 },
+            coord: Some(Coord {
+                x: 1,
+                y: 2,
+                z: None,
+            }),
             host: String::from("host"),
             game_id,
+            nonce: None,
         },
     );
 
@@ -398,7 +532,7 @@ fn accept_with_different_funds() {
 fn query_by_invalid_host() {
     // GIVEN
     let mut deps = mock_dependencies();
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg::default();
     let info = mock_info("host", &coins(2, "token"));
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -428,7 +562,7 @@ fn query_by_invalid_host() {
 fn query_by_invalid_opponent() {
     // GIVEN
     let mut deps = mock_dependencies();
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg::default();
     let info = mock_info("host", &coins(2, "token"));
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -453,3 +587,4203 @@ fn query_by_invalid_opponent() {
         }
     );
 }
+
+#[test]
+fn query_games_by_status() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    invite(&mut deps, "host", "player");
+    invite(&mut deps, "player", "opponent");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("player", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 1,
+                y: 1,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: 2,
+            nonce: None,
+        },
+    )
+    .unwrap();
+
+    // WHEN
+    let invited = query_games(&deps, Some(Status::INVITED), None, None, None);
+    let playing = query_games(&deps, Some(Status::PLAYING), None, None, None);
+
+    // THEN
+    assert_eq!(game_ids(&invited), vec![1, 3]);
+    assert_eq!(game_ids(&playing), vec![2]);
+    assert_eq!(playing[0].host, Addr::unchecked("host"));
+    assert_eq!(playing[0].opponent, Addr::unchecked("player"));
+}
+
+#[test]
+fn query_games_by_player() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    invite(&mut deps, "other", "player");
+    invite(&mut deps, "player", "host");
+    invite(&mut deps, "third", "host");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Reject {
+            as_host: false,
+            opponent: String::from("player"),
+            game_id: 3,
+        },
+    )
+    .unwrap();
+
+    // WHEN
+    let all = query_games(&deps, None, Some("host"), None, None);
+    let rejected = query_games(&deps, Some(Status::REJECTED), Some("host"), None, None);
+
+    // THEN
+    assert_eq!(game_ids(&all), vec![1, 3, 4]);
+    assert_eq!(game_ids(&rejected), vec![3]);
+}
+
+#[test]
+fn query_games_paginated() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    for host in ["host", "player", "other", "third", "fourth"] {
+        invite(&mut deps, host, "opponent");
+    }
+
+    // WHEN
+    let first_page = query_games(&deps, None, Some("opponent"), None, Some(2));
+    let second_page = query_games(&deps, None, Some("opponent"), Some(2), Some(2));
+    let last_page = query_games(&deps, Some(Status::INVITED), None, Some(4), Some(2));
+
+    // THEN
+    assert_eq!(game_ids(&first_page), vec![1, 2]);
+    assert_eq!(game_ids(&second_page), vec![3, 4]);
+    assert_eq!(game_ids(&last_page), vec![5]);
+}
+
+#[test]
+fn query_player_games_inbox() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "player");
+    invite(&mut deps, "player", "third");
+    invite(&mut deps, "other", "player");
+    invite(&mut deps, "opponent", "player");
+    for (host, game_id) in [("other", 3), ("opponent", 4)] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player", &coins(2, "token")),
+            ExecuteMsg::Accept {
+                coord: Some(Coord {
+                    x: 1,
+                    y: 1,
+                    z: None,
+                }),
+                host: String::from(host),
+                game_id,
+                nonce: None,
+            },
+        )
+        .unwrap();
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Play {
+            as_host: true,
+            coord: Coord {
+                x: 2,
+                y: 2,
+                z: None,
+            },
+            opponent: String::from("player"),
+            game_id: 4,
+            symbol: None,
+        },
+    )
+    .unwrap();
+
+    // WHEN
+    let inbox = query_player_games(&deps, "player", None, None, None);
+    let hosted = query_player_games(&deps, "player", Some(Role::Host), None, None);
+
+    // THEN
+    assert_eq!(game_ids(&inbox.invitations), vec![1]);
+    assert_eq!(game_ids(&inbox.my_turn), vec![4]);
+    assert_eq!(game_ids(&inbox.waiting), vec![3, 2]);
+    assert_eq!(inbox.history, vec![]);
+    assert_eq!(hosted.invitations, vec![]);
+    assert_eq!(game_ids(&hosted.waiting), vec![2]);
+}
+
+#[test]
+fn query_player_games_history() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    for (host, as_host, game_id) in [("host", false, 1), ("player", true, 2)] {
+        let opponent = if host == "host" { "player" } else { "host" };
+        invite(&mut deps, host, opponent);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player", &[]),
+            ExecuteMsg::Reject {
+                as_host,
+                opponent: String::from("host"),
+                game_id,
+            },
+        )
+        .unwrap();
+    }
+    invite(&mut deps, "host", "player");
+
+    // WHEN
+    let history = query_player_games(&deps, "player", None, Some(Status::REJECTED), None);
+    let older = query_player_games(&deps, "player", None, Some(Status::REJECTED), Some(2));
+    let invited = query_player_games(
+        &deps,
+        "player",
+        Some(Role::Opponent),
+        Some(Status::INVITED),
+        None,
+    );
+
+    // THEN
+    assert_eq!(game_ids(&history.history), vec![2, 1]);
+    assert_eq!(game_ids(&older.history), vec![1]);
+    assert_eq!(history.invitations, vec![]);
+    assert_eq!(game_ids(&invited.invitations), vec![3]);
+    assert_eq!(invited.history, vec![]);
+}
+
+#[test]
+fn invite_with_open_game_between_same_players() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            opponent: String::from("host"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
+        },
+    );
+
+    // THEN
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::GameAlreadyInProgress {
+            host: Addr::unchecked("opponent"),
+            opponent: Addr::unchecked("host")
+        }
+    );
+    assert_eq!(query_active_games(&deps, "opponent", "host"), vec![1]);
+}
+
+#[test]
+fn invite_after_open_game_is_rejected() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Reject {
+            as_host: false,
+            opponent: String::from("host"),
+            game_id: GAME_ID,
+        },
+    )
+    .unwrap();
+
+    // WHEN
+    invite(&mut deps, "host", "opponent");
+
+    // THEN
+    assert_eq!(query_active_games(&deps, "host", "opponent"), vec![2]);
+}
+
+#[test]
+fn update_config_max_open_games_per_pair() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let unauthorized = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::UpdateConfig {
+            max_open_games_per_pair: Some(2),
+            reveal_timeout: None,
+            house: None,
+            treasury: None,
+            fee_bps: None,
+            stake_limits: None,
+        },
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            max_open_games_per_pair: Some(2),
+            reveal_timeout: None,
+            house: None,
+            treasury: None,
+            fee_bps: None,
+            stake_limits: None,
+        },
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    invite(&mut deps, "opponent", "host");
+
+    // THEN
+    assert_eq!(unauthorized.unwrap_err(), ContractError::Unauthorized {});
+    assert_eq!(query_active_games(&deps, "host", "opponent"), vec![1, 2]);
+}
+
+#[test]
+fn query_game_moves() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    let mut env = mock_env();
+    env.block.height += 1;
+    env.block.time = env.block.time.plus_seconds(5);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 1,
+                y: 1,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+
+    // WHEN
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GameMoves { game_id: GAME_ID },
+    );
+
+    // THEN
+    let moves: Vec<Move> = from_binary(&res.unwrap()).unwrap();
+    assert_eq!(
+        moves,
+        vec![
+            Move {
+                player: PlayerSymbol::X,
+                coord: Coord {
+                    x: 0,
+                    y: 0,
+                    z: None
+                },
+                symbol: None,
+                height: mock_env().block.height,
+                time: mock_env().block.time,
+            },
+            Move {
+                player: PlayerSymbol::O,
+                coord: Coord {
+                    x: 1,
+                    y: 1,
+                    z: None
+                },
+                symbol: None,
+                height: env.block.height,
+                time: env.block.time,
+            }
+        ]
+    );
+}
+
+#[test]
+fn query_replay_at() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 1,
+                y: 1,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Play {
+            as_host: true,
+            coord: Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            },
+            opponent: String::from("opponent"),
+            game_id: GAME_ID,
+            symbol: None,
+        },
+    )
+    .unwrap();
+
+    // WHEN
+    let replay = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ReplayAt {
+            game_id: GAME_ID,
+            move_index: 1,
+        },
+    );
+    let unknown_move = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ReplayAt {
+            game_id: GAME_ID,
+            move_index: 3,
+        },
+    );
+    let last_index = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ReplayAt {
+            game_id: GAME_ID,
+            move_index: u32::MAX,
+        },
+    );
+
+    // THEN
+    let replay: ReplayResponse = from_binary(&replay.unwrap()).unwrap();
+    assert_eq!(
+        replay.last_move.coord,
+        Coord {
+            x: 1,
+            y: 1,
+            z: None
+        }
+    );
+    assert_eq!(
+        replay.board,
+        vec![
+            vec![Some(PlayerSymbol::X), None, None],
+            vec![None, Some(PlayerSymbol::O), None],
+            vec![None, None, None]
+        ]
+    );
+    assert_eq!(
+        unknown_move.unwrap_err(),
+        StdError::not_found("move 3 of game 1")
+    );
+    assert_eq!(
+        last_index.unwrap_err(),
+        StdError::not_found(format!("move {} of game 1", u32::MAX))
+    );
+}
+
+#[test]
+fn invite_with_invalid_ruleset() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            opponent: String::from("opponent"),
+            ruleset: Some(Ruleset {
+                board_size: 4,
+                win_length: 5,
+                ..Ruleset::default()
+            }),
+            first_player: None,
+            commitment: None,
+        },
+    );
+
+    // THEN
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidRuleset { min: 3, max: 19 }
+    );
+}
+
+#[test]
+fn play_gomoku_until_five_in_a_row() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 10,
+                y: 10,
+                z: None,
+            }),
+            opponent: String::from("opponent"),
+            ruleset: Some(Ruleset {
+                board_size: 15,
+                win_length: 5,
+                ..Ruleset::default()
+            }),
+            first_player: None,
+            commitment: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+    let out_of_board = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Play {
+            as_host: true,
+            coord: Coord {
+                x: 15,
+                y: 0,
+                z: None,
+            },
+            opponent: String::from("opponent"),
+            game_id: GAME_ID,
+            symbol: None,
+        },
+    );
+    for (host_move, opponent_move) in [(11, 1), (12, 2), (13, 3)] {
+        play(&mut deps, "host", true, (host_move, host_move));
+        play(&mut deps, "opponent", false, (opponent_move, 0));
+    }
+
+    // WHEN
+    let res = play(&mut deps, "host", true, (14, 14));
+
+    // THEN
+    assert_eq!(
+        out_of_board.unwrap_err(),
+        ContractError::InvalidCoord {
+            coord: Coord {
+                x: 15,
+                y: 0,
+                z: None
+            }
+        }
+    );
+    assert_eq!(
+        res.attributes
+            .iter()
+            .find(|attribute| attribute.key == "winner")
+            .map(|attribute| attribute.value.as_str()),
+        Some("X")
+    );
+}
+
+#[test]
+fn game_without_ruleset_defaults_to_classic() {
+    // GIVEN
+    let legacy_game = r#"{
+        "host": "host",
+        "opponent": "opponent",
+        "board": [[null, null, "X"], [null, null, null], [null, null, null]],
+        "host_symbol": "X",
+        "player_round": "O",
+        "prize": [],
+        "status": "INVITED",
+        "winner": null
+    }"#;
+
+    // WHEN
+    let game: Game = cosmwasm_std::from_slice(legacy_game.as_bytes()).unwrap();
+
+    // THEN
+    assert_eq!(game.ruleset, Ruleset::default());
+    assert_eq!(game.ruleset.board_size, 3);
+    assert_eq!(game.ruleset.win_length, 3);
+}
+
+#[test]
+fn migrate_legacy_games() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    cw2::set_contract_version(deps.as_mut().storage, "tic-tac-toe", "0.1.0").unwrap();
+    GAMES_COUNT.save(deps.as_mut().storage, &2).unwrap();
+    let (host, opponent) = (Addr::unchecked("host"), Addr::unchecked("opponent"));
+    for (game_id, status, prize) in [(1, Status::PLAYING, 4), (2, Status::COMPLETED, 4)] {
+        LEGACY_GAMES
+            .save(
+                deps.as_mut().storage,
+                (&host, &opponent, game_id),
+                &LegacyGame {
+                    board: vec![
+                        vec![Some(PlayerSymbol::X), None, None],
+                        vec![None, Some(PlayerSymbol::O), None],
+                        vec![None, None, None],
+                    ],
+                    host_symbol: PlayerSymbol::X,
+                    player_round: Some(PlayerSymbol::X),
+                    prize: coins(prize, "token"),
+                    status,
+                    winner: None,
+                },
+            )
+            .unwrap();
+    }
+
+    // WHEN
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            admin: Some(String::from("admin")),
+        },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(res.attributes[1].value, "2");
+    assert_eq!(
+        LEGACY_GAMES
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .count(),
+        0
+    );
+    let games = query_games(&deps, None, Some("host"), None, None);
+    assert_eq!(game_ids(&games), vec![1, 2]);
+    assert_eq!(games[0].game.opponent, opponent);
+    assert_eq!(games[0].game.status, Status::PLAYING);
+    assert_eq!(games[0].game.ruleset, Ruleset::default());
+    assert_eq!(games[0].game.host_stake, vec![]);
+    assert_eq!(games[1].game.status, Status::COMPLETED);
+    assert_eq!(query_active_games(&deps, "host", "opponent"), vec![1]);
+    let version: cw2::ContractVersion =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetContractVersion {}).unwrap())
+            .unwrap();
+    assert_eq!(version.version, "0.2.0");
+
+    play(&mut deps, "host", true, (2, 2));
+    let game = query_games(&deps, None, Some("host"), None, None).remove(0);
+    assert_eq!(game.game.board[2][2], Some(PlayerSymbol::X));
+}
+
+#[test]
+fn play_ultimate_on_forced_sub_board() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite_ultimate(
+        &mut deps,
+        Coord {
+            x: 4,
+            y: 4,
+            z: None,
+        },
+    );
+
+    // WHEN
+    let outside_forced_board = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 5,
+                y: 3,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        outside_forced_board.unwrap_err(),
+        ContractError::SubBoardNotPlayable {
+            coord: Coord {
+                x: 0,
+                y: 0,
+                z: None
+            }
+        }
+    );
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.board.len(), 9);
+    assert_eq!(
+        game.forced_board,
+        Some(Coord {
+            x: 2,
+            y: 0,
+            z: None
+        })
+    );
+}
+
+#[test]
+fn play_ultimate_until_three_sub_boards_in_a_row() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite_ultimate(
+        &mut deps,
+        Coord {
+            x: 8,
+            y: 2,
+            z: None,
+        },
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 6,
+                y: 6,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+    let moves = [
+        (1, 0),
+        (3, 2),
+        (0, 8),
+        (0, 7),
+        (2, 5),
+        (6, 8),
+        (1, 7),
+        (3, 5),
+        (2, 6),
+        (6, 2),
+        (3, 1),
+        (0, 4),
+        (2, 4),
+        (6, 4),
+        (2, 3),
+        (6, 0),
+        (0, 0),
+        (0, 2),
+    ];
+    for (round, coord) in moves.into_iter().enumerate() {
+        let as_host = round % 2 == 0;
+        play(
+            &mut deps,
+            if as_host { "host" } else { "opponent" },
+            as_host,
+            coord,
+        );
+    }
+
+    // WHEN
+    let res = play(&mut deps, "host", true, (2, 0));
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::COMPLETED);
+    assert_eq!(game.winner, Some(PlayerSymbol::X));
+    assert_eq!(
+        game.meta_board,
+        vec![
+            vec![Some(PlayerSymbol::X), None, None],
+            vec![Some(PlayerSymbol::X), None, None],
+            vec![Some(PlayerSymbol::X), None, None]
+        ]
+    );
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn invite_with_coord_not_matching_variant() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let classic_with_layer = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: Some(0),
+            }),
+            opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
+        },
+    );
+    let qubic_without_layer = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            opponent: String::from("opponent"),
+            ruleset: Some(qubic_ruleset()),
+            first_player: None,
+            commitment: None,
+        },
+    );
+
+    // THEN
+    assert_eq!(
+        classic_with_layer.unwrap_err(),
+        ContractError::InvalidCoord {
+            coord: Coord {
+                x: 0,
+                y: 0,
+                z: Some(0)
+            }
+        }
+    );
+    assert_eq!(
+        qubic_without_layer.unwrap_err(),
+        ContractError::InvalidCoord {
+            coord: Coord {
+                x: 0,
+                y: 0,
+                z: None
+            }
+        }
+    );
+}
+
+#[test]
+fn play_qubic_until_space_diagonal() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: Some(0),
+            }),
+            opponent: String::from("opponent"),
+            ruleset: Some(qubic_ruleset()),
+            first_player: None,
+            commitment: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: Some(1),
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+    for (round, (x, y, z)) in [(1, 1, 1), (0, 0, 2), (2, 2, 2), (0, 0, 3)]
+        .into_iter()
+        .enumerate()
+    {
+        let as_host = round % 2 == 0;
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(if as_host { "host" } else { "opponent" }, &[]),
+            ExecuteMsg::Play {
+                as_host,
+                coord: Coord { x, y, z: Some(z) },
+                opponent: String::from(if as_host { "opponent" } else { "host" }),
+                game_id: GAME_ID,
+                symbol: None,
+            },
+        )
+        .unwrap();
+    }
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Play {
+            as_host: true,
+            coord: Coord {
+                x: 3,
+                y: 3,
+                z: Some(3),
+            },
+            opponent: String::from("opponent"),
+            game_id: GAME_ID,
+            symbol: None,
+        },
+    )
+    .unwrap();
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.board.len(), 16);
+    assert_eq!(game.board[15][3], Some(PlayerSymbol::X));
+    assert_eq!(game.status, Status::COMPLETED);
+    assert_eq!(game.winner, Some(PlayerSymbol::X));
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn play_misere_completing_a_line_loses() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite_with_ruleset(
+        &mut deps,
+        Ruleset {
+            misere: true,
+            ..Ruleset::default()
+        },
+    );
+    accept_invite(&mut deps, (1, 1));
+    play(&mut deps, "host", true, (1, 0));
+    play(&mut deps, "opponent", false, (0, 2));
+
+    // WHEN
+    let res = play(&mut deps, "host", true, (2, 0));
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::COMPLETED);
+    assert_eq!(game.winner, Some(PlayerSymbol::O));
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("opponent"),
+            amount: coins(4, "token"),
+        })
+    );
+}
+
+#[test]
+fn play_wild_with_chosen_symbol() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite_with_ruleset(
+        &mut deps,
+        Ruleset {
+            wild: true,
+            ..Ruleset::default()
+        },
+    );
+    accept_invite(&mut deps, (1, 1));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Play {
+            as_host: true,
+            coord: Coord {
+                x: 1,
+                y: 0,
+                z: None,
+            },
+            opponent: String::from("opponent"),
+            game_id: GAME_ID,
+            symbol: Some(PlayerSymbol::X),
+        },
+    )
+    .unwrap();
+    let without_symbol = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Play {
+            as_host: false,
+            coord: Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            },
+            opponent: String::from("host"),
+            game_id: GAME_ID,
+            symbol: None,
+        },
+    );
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Play {
+            as_host: false,
+            coord: Coord {
+                x: 2,
+                y: 0,
+                z: None,
+            },
+            opponent: String::from("host"),
+            game_id: GAME_ID,
+            symbol: Some(PlayerSymbol::X),
+        },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        without_symbol.unwrap_err(),
+        ContractError::SymbolRequired {}
+    );
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.board[0], vec![Some(PlayerSymbol::X); 3]);
+    assert_eq!(game.winner, Some(PlayerSymbol::O));
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("opponent"),
+            amount: coins(4, "token"),
+        })
+    );
+}
+
+#[test]
+fn play_symbol_on_game_that_is_not_wild() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    accept_invite(&mut deps, (1, 1));
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Play {
+            as_host: true,
+            coord: Coord {
+                x: 1,
+                y: 0,
+                z: None,
+            },
+            opponent: String::from("opponent"),
+            game_id: GAME_ID,
+            symbol: Some(PlayerSymbol::O),
+        },
+    );
+
+    // THEN
+    assert_eq!(res.unwrap_err(), ContractError::SymbolNotAllowed {});
+}
+
+#[test]
+fn play_notakto_killing_last_board_loses() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    start_notakto(&mut deps, &[(1, 0, 0), (2, 0, 0), (0, 0, 1), (1, 1, 1)]);
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Play {
+            as_host: false,
+            coord: Coord {
+                x: 2,
+                y: 2,
+                z: Some(1),
+            },
+            opponent: String::from("host"),
+            game_id: GAME_ID,
+            symbol: None,
+        },
+    )
+    .unwrap();
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.board[5][2], Some(PlayerSymbol::X));
+    assert_eq!(
+        game.meta_board,
+        vec![vec![Some(PlayerSymbol::X), Some(PlayerSymbol::O)]]
+    );
+    assert_eq!(game.status, Status::COMPLETED);
+    assert_eq!(game.winner, Some(PlayerSymbol::X));
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(4, "token"),
+        })
+    );
+}
+
+#[test]
+fn play_notakto_on_dead_board() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    start_notakto(&mut deps, &[(1, 0, 0), (2, 0, 0)]);
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Play {
+            as_host: false,
+            coord: Coord {
+                x: 1,
+                y: 1,
+                z: Some(0),
+            },
+            opponent: String::from("host"),
+            game_id: GAME_ID,
+            symbol: Some(PlayerSymbol::X),
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::SubBoardNotPlayable {
+            coord: Coord {
+                x: 1,
+                y: 1,
+                z: Some(0),
+            },
+        }
+    );
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::PLAYING);
+    assert_eq!(game.board[0], vec![Some(PlayerSymbol::X); 3]);
+    assert_eq!(game.meta_board, vec![vec![Some(PlayerSymbol::X), None]]);
+}
+
+#[test]
+fn accept_with_first_move_of_opponent() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite_first(&mut deps, FirstPlayer::Opponent);
+
+    // WHEN
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 1,
+                y: 1,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::PLAYING);
+    assert_eq!(game.host_symbol, PlayerSymbol::O);
+    assert_eq!(game.board[1][1], Some(PlayerSymbol::X));
+    assert_eq!(game.board.iter().flatten().flatten().count(), 1);
+    assert!(game.is_turn_of(&Addr::unchecked("host")));
+    let moves: Vec<Move> = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GameMoves { game_id: GAME_ID },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].player, PlayerSymbol::X);
+}
+
+#[test]
+fn accept_with_move_when_host_moves_first() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite_first(&mut deps, FirstPlayer::Host);
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 1,
+                y: 1,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::FirstMoveNotAllowed {});
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::INVITED);
+}
+
+#[test]
+fn invite_with_move_when_opponent_moves_first() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: Some(FirstPlayer::Opponent),
+            commitment: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::FirstMoveNotAllowed {});
+}
+
+#[test]
+fn invite_with_random_first_player_without_coin_flip() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: None,
+            opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: Some(FirstPlayer::Random),
+            commitment: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::CoinFlipRequired {});
+    assert!(query_games(&deps, None, None, None, None).is_empty());
+}
+
+#[test]
+fn reveal_coin_flip_decides_first_player() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    start_coin_flip(&mut deps, &SECRET, &[1]);
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::REVEALING);
+    assert!(game.is_turn_of(&Addr::unchecked("host")));
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Reveal {
+            game_id: GAME_ID,
+            secret: Binary::from(SECRET),
+        },
+    )
+    .unwrap();
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::PLAYING);
+    assert_eq!(game.host_symbol, PlayerSymbol::O);
+    assert!(game.is_turn_of(&Addr::unchecked("opponent")));
+    assert_eq!(res.attributes[2].value, "opponent");
+}
+
+#[test]
+fn reveal_secret_not_matching_commitment() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    start_coin_flip(&mut deps, &SECRET, &[1]);
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::Reveal {
+            game_id: GAME_ID,
+            secret: Binary::from([2u8; 16]),
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::InvalidSecret { min_length: 16 });
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::REVEALING);
+}
+
+#[test]
+fn claim_timeout_before_reveal_deadline() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    start_coin_flip(&mut deps, &SECRET, &[1]);
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::ClaimTimeout { game_id: GAME_ID },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::RevealNotExpired {
+            deadline: mock_env().block.time.plus_seconds(24 * 60 * 60),
+        }
+    );
+}
+
+#[test]
+fn claim_timeout_when_host_does_not_reveal() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg {
+            max_open_games_per_pair: None,
+            reveal_timeout: Some(60),
+            treasury: None,
+            fee_bps: None,
+        },
+    )
+    .unwrap();
+    start_coin_flip(&mut deps, &SECRET, &[1]);
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(61);
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("opponent", &[]),
+        ExecuteMsg::ClaimTimeout { game_id: GAME_ID },
+    )
+    .unwrap();
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::COMPLETED);
+    assert_eq!(game.winner, Some(game.host_symbol.opposite()));
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("opponent"),
+            amount: coins(4, "token"),
+        })
+    );
+    assert!(query_active_games(&deps, "host", "opponent").is_empty());
+}
+
+#[test]
+fn rematch_swaps_first_player() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    complete_game(&mut deps);
+
+    // WHEN
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Rematch { game_id: GAME_ID },
+    )
+    .unwrap();
+
+    // THEN
+    let history: Vec<GameResponse> = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RematchHistory { game_id: 2 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(game_ids(&history), vec![1, 2]);
+    let rematch = &history[1];
+    assert_eq!(rematch.host, Addr::unchecked("opponent"));
+    assert_eq!(rematch.opponent, Addr::unchecked("host"));
+    assert_eq!(rematch.game.status, Status::INVITED);
+    assert_eq!(rematch.game.previous_game, Some(GAME_ID));
+    assert_eq!(rematch.game.prize, coins(2, "token"));
+    assert_eq!(rematch.game.board.iter().flatten().flatten().count(), 0);
+    assert_eq!(rematch.game.first_player, FirstPlayer::Host);
+    assert_eq!(rematch.game.player_of(PlayerSymbol::X), &rematch.host);
+}
+
+#[test]
+fn rematch_already_started() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg {
+            max_open_games_per_pair: Some(5),
+            reveal_timeout: None,
+            treasury: None,
+            fee_bps: None,
+        },
+    )
+    .unwrap();
+    complete_game(&mut deps);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Rematch { game_id: GAME_ID },
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Rematch { game_id: GAME_ID },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::RematchAlreadyStarted { game_id: GAME_ID }
+    );
+}
+
+#[test]
+fn play_series_until_majority() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    start_series(&mut deps, 3);
+    play_moves(
+        &mut deps,
+        1,
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+            (true, (0, 2)),
+        ],
+    );
+    play_moves(
+        &mut deps,
+        2,
+        &[
+            (false, (1, 0)),
+            (true, (0, 0)),
+            (false, (2, 2)),
+            (true, (0, 1)),
+            (false, (2, 0)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves(&mut deps, 2, &[(true, (0, 2))]);
+
+    // THEN
+    let series: SeriesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Series { id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(series.series.status, Status::COMPLETED);
+    assert_eq!(series.series.host_wins, 2);
+    assert_eq!(series.series.opponent_wins, 0);
+    assert_eq!(series.series.winner, Some(Addr::unchecked("host")));
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(4, "token"),
+        })
+    );
+    let series_games: Vec<GameResponse> =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::SeriesGames { id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(game_ids(&series_games), vec![1, 2]);
+    assert_eq!(series_games[0].game.first_player, FirstPlayer::Host);
+    assert_eq!(series_games[1].game.first_player, FirstPlayer::Opponent);
+    assert!(series_games.iter().all(|game| game.game.prize.is_empty()));
+}
+
+#[test]
+fn start_series_with_open_game_between_same_players() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+
+    // WHEN
+    start_series(&mut deps, 3);
+
+    // THEN
+    let series: SeriesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Series { id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(series.series.game_ids, vec![2]);
+    assert_eq!(query_active_games(&deps, "host", "opponent"), vec![1, 2]);
+}
+
+#[test]
+fn create_series_with_even_best_of() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::CreateSeries {
+            opponent: String::from("opponent"),
+            best_of: 4,
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::InvalidSeries { max: 15 });
+}
+
+#[test]
+fn create_series_without_stake() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::CreateSeries {
+            opponent: String::from("opponent"),
+            best_of: 3,
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::StakeRequired {});
+}
+
+#[test]
+fn claim_series_move_timeout() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    start_series(&mut deps, 1);
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(24 * 60 * 60 + 1);
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("opponent", &[]),
+        ExecuteMsg::ClaimTimeout { game_id: 1 },
+    )
+    .unwrap();
+
+    // THEN
+    let series: SeriesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Series { id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(series.series.status, Status::COMPLETED);
+    assert_eq!(series.series.winner, Some(Addr::unchecked("opponent")));
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("opponent"),
+            amount: coins(4, "token"),
+        })]
+    );
+}
+
+#[test]
+fn auto_match_with_open_invite() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    auto_match(&mut deps, "host");
+    assert_eq!(query_lobby(&deps, Some("token"), None).len(), 1);
+
+    // WHEN
+    auto_match(&mut deps, "opponent");
+
+    // THEN
+    assert!(query_lobby(&deps, Some("token"), None).is_empty());
+    let game = query_games(&deps, None, None, None, None).remove(0);
+    assert_eq!(game.host, Addr::unchecked("host"));
+    assert_eq!(game.opponent, Addr::unchecked("opponent"));
+    assert_eq!(game.game.status, Status::PLAYING);
+    assert_eq!(game.game.first_player, FirstPlayer::Host);
+    assert_eq!(game.game.prize, coins(4, "token"));
+}
+
+#[test]
+fn auto_match_past_private_invites() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    for host in 0..30 {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&format!("host{}", host), &coins(2, "token")),
+            ExecuteMsg::OpenInvite {
+                ruleset: None,
+                first_player: None,
+                allow_list: Some(vec![String::from("friend")]),
+            },
+        )
+        .unwrap();
+    }
+    auto_match(&mut deps, "host");
+
+    // WHEN
+    auto_match(&mut deps, "opponent");
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0);
+    assert_eq!(game.host, Addr::unchecked("host"));
+    assert_eq!(game.opponent, Addr::unchecked("opponent"));
+}
+
+#[test]
+fn auto_match_past_scan_limit() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    for _ in 0..MAX_AUTO_MATCH_SCAN {
+        auto_match(&mut deps, "host");
+    }
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::AutoMatch {
+            stake: coin(2, "token"),
+        },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        res.attributes[1].value,
+        (MAX_AUTO_MATCH_SCAN + 1).to_string()
+    );
+    assert!(query_games(&deps, None, None, None, None).is_empty());
+}
+
+#[test]
+fn accept_open_invite_outside_allow_list() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::OpenInvite {
+            ruleset: None,
+            first_player: None,
+            allow_list: Some(vec![String::from("friend")]),
+        },
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::AcceptOpenInvite {
+            invite_id: 1,
+            coord: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::Unauthorized {});
+    assert_eq!(query_lobby(&deps, None, None).len(), 1);
+}
+
+#[test]
+fn query_lobby_with_min_stake() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    for (host, stake) in [
+        ("host", coin(2, "token")),
+        ("whale", coin(5, "token")),
+        ("other", coin(9, "atom")),
+        ("shark", coin(4, "token")),
+    ] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(host, &[stake]),
+            ExecuteMsg::OpenInvite {
+                ruleset: None,
+                first_player: None,
+                allow_list: None,
+            },
+        )
+        .unwrap();
+    }
+
+    // WHEN
+    let lobby = query_lobby(&deps, Some("token"), Some(3));
+
+    // THEN
+    assert_eq!(lobby.len(), 2);
+    assert_eq!(lobby[0].invite_id, 4);
+    assert_eq!(lobby[1].invite_id, 2);
+    assert_eq!(lobby[1].invite.host, Addr::unchecked("whale"));
+}
+
+#[test]
+fn query_lobby_with_min_stake_without_denom() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Lobby {
+            denom: None,
+            min_stake: Some(Uint128::new(3)),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        StdError::generic_err("min_stake can only be given together with a denom")
+    );
+}
+
+#[test]
+fn play_tournament_with_bye() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    create_tournament(&mut deps, 3);
+    join_tournament(&mut deps, "host");
+    join_tournament(&mut deps, "opponent");
+    join_tournament(&mut deps, "third");
+    let tournament = query_tournament(&deps);
+    assert_eq!(tournament.status, Status::PLAYING);
+    assert_eq!(tournament.byes, vec![Addr::unchecked("host")]);
+    play_moves_between(
+        &mut deps,
+        1,
+        ("opponent", "third"),
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+            (true, (0, 2)),
+        ],
+    );
+    assert_eq!(query_tournament(&deps).round, 2);
+    play_moves(
+        &mut deps,
+        2,
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves(&mut deps, 2, &[(true, (0, 2))]);
+
+    // THEN
+    let tournament = query_tournament(&deps);
+    assert_eq!(tournament.status, Status::COMPLETED);
+    assert_eq!(tournament.winner, Some(Addr::unchecked("host")));
+    assert_eq!(
+        tournament.eliminated,
+        vec![Addr::unchecked("third"), Addr::unchecked("opponent")]
+    );
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|message| message.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: coins(5, "token"),
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("opponent"),
+                amount: coins(1, "token"),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn draw_tournament_match_up_to_limit() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    create_tournament(&mut deps, 2);
+    join_tournament(&mut deps, "host");
+    join_tournament(&mut deps, "opponent");
+    let draw: Vec<(bool, (u8, u8))> = vec![
+        (true, (0, 0)),
+        (false, (1, 0)),
+        (true, (2, 0)),
+        (false, (1, 1)),
+        (true, (0, 1)),
+        (false, (2, 1)),
+        (true, (1, 2)),
+        (false, (0, 2)),
+        (true, (2, 2)),
+    ];
+    let swapped_draw: Vec<(bool, (u8, u8))> = draw
+        .iter()
+        .map(|(as_host, coord)| (!as_host, *coord))
+        .collect();
+    play_moves(&mut deps, 1, &draw);
+    play_moves(&mut deps, 2, &swapped_draw);
+    assert_eq!(query_tournament(&deps).matches[0].game_id, 3);
+
+    // WHEN
+    play_moves(&mut deps, 3, &draw);
+
+    // THEN
+    let tournament = query_tournament(&deps);
+    assert_eq!(tournament.matches[0].draws, 3);
+    assert_eq!(tournament.status, Status::COMPLETED);
+    assert_eq!(tournament.winner, Some(Addr::unchecked("host")));
+    assert_eq!(tournament.eliminated, vec![Addr::unchecked("opponent")]);
+}
+
+#[test]
+fn claim_tournament_move_timeout() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    create_tournament(&mut deps, 2);
+    join_tournament(&mut deps, "host");
+    join_tournament(&mut deps, "opponent");
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(24 * 60 * 60);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::ClaimTimeout { game_id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MoveNotExpired {
+            deadline: env.block.time
+        }
+    );
+
+    // WHEN
+    env.block.time = env.block.time.plus_seconds(1);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("organizer", &[]),
+        ExecuteMsg::ClaimTimeout { game_id: 1 },
+    )
+    .unwrap();
+
+    // THEN
+    let tournament = query_tournament(&deps);
+    assert_eq!(tournament.status, Status::COMPLETED);
+    assert_eq!(tournament.winner, Some(Addr::unchecked("opponent")));
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("opponent"),
+                amount: coins(3, "token"),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: coins(1, "token"),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn create_tournament_with_zero_entry_fee() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::CreateTournament {
+            entry_fee: coin(0, "token"),
+            max_players: 4,
+            start_time: mock_env().block.time,
+            payout_split: vec![100],
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::InvalidTournament {
+            reason: String::from("entry_fee must not be zero"),
+        }
+    );
+}
+
+#[test]
+fn start_tournament_before_start_time() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    create_tournament(&mut deps, 4);
+    join_tournament(&mut deps, "host");
+    join_tournament(&mut deps, "opponent");
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        ExecuteMsg::StartTournament { tournament_id: 1 },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::TournamentNotStarted {
+            start_time: mock_env().block.time.plus_seconds(3600),
+        }
+    );
+}
+
+#[test]
+fn create_tournament_with_invalid_payout_split() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::CreateTournament {
+            entry_fee: coin(2, "token"),
+            max_players: 4,
+            start_time: mock_env().block.time,
+            payout_split: vec![60, 30],
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::InvalidTournament {
+            reason: String::from("payout_split must add up to 100"),
+        }
+    );
+}
+
+#[test]
+fn play_double_round_robin_league() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::CreateLeague {
+            entry_fee: coin(2, "token"),
+            max_players: 2,
+            start_time: mock_env().block.time,
+            double_round_robin: true,
+            points: None,
+            payout_split: vec![100],
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap();
+    for player in ["host", "opponent"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(player, &coins(2, "token")),
+            ExecuteMsg::JoinLeague { league_id: 1 },
+        )
+        .unwrap();
+    }
+    let games = query_games(&deps, None, None, None, None);
+    assert_eq!(game_ids(&games), vec![1, 2]);
+    assert_eq!(games[1].host, Addr::unchecked("opponent"));
+    play_moves(
+        &mut deps,
+        1,
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+            (true, (0, 2)),
+        ],
+    );
+    play_moves_between(
+        &mut deps,
+        2,
+        ("opponent", "host"),
+        &[
+            (true, (0, 0)),
+            (false, (1, 1)),
+            (true, (2, 2)),
+            (false, (0, 1)),
+            (true, (2, 1)),
+            (false, (2, 0)),
+            (true, (1, 0)),
+            (false, (1, 2)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves_between(&mut deps, 2, ("opponent", "host"), &[(true, (0, 2))]);
+
+    // THEN
+    let standings: StandingsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Standings { league_id: 1 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(standings.status, Status::COMPLETED);
+    assert_eq!(standings.winner, Some(Addr::unchecked("host")));
+    assert_eq!(standings.games_left, 0);
+    assert_eq!(
+        standings
+            .standings
+            .iter()
+            .map(|standing| (standing.player.as_str(), standing.points, standing.draws))
+            .collect::<Vec<_>>(),
+        vec![("host", 4, 1), ("opponent", 1, 1)]
+    );
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(4, "token"),
+        })
+    );
+}
+
+#[test]
+fn win_series_with_platform_fee() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate_with_fee(&mut deps, 2_500);
+    start_series(&mut deps, 1);
+    play_moves(
+        &mut deps,
+        1,
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves(&mut deps, 1, &[(true, (0, 2))]);
+
+    // THEN
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(3, "token"),
+        })]
+    );
+}
+
+#[test]
+fn win_tournament_with_platform_fee() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate_with_fee(&mut deps, 5_000);
+    create_tournament(&mut deps, 2);
+    join_tournament(&mut deps, "host");
+    join_tournament(&mut deps, "opponent");
+    play_moves(
+        &mut deps,
+        1,
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves(&mut deps, 1, &[(true, (0, 2))]);
+
+    // THEN
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(2, "token"),
+        })]
+    );
+}
+
+#[test]
+fn claim_league_move_timeout() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::CreateLeague {
+            entry_fee: coin(2, "token"),
+            max_players: 2,
+            start_time: mock_env().block.time,
+            double_round_robin: false,
+            points: None,
+            payout_split: vec![100],
+            ruleset: None,
+            move_timeout: Some(60),
+        },
+    )
+    .unwrap();
+    for player in ["host", "opponent"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(player, &coins(2, "token")),
+            ExecuteMsg::JoinLeague { league_id: 1 },
+        )
+        .unwrap();
+    }
+    play_moves(&mut deps, 1, &[(true, (0, 0))]);
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(61);
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("organizer", &[]),
+        ExecuteMsg::ClaimTimeout { game_id: 1 },
+    )
+    .unwrap();
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0);
+    assert_eq!(game.game.status, Status::COMPLETED);
+    assert_eq!(game.game.winner, Some(PlayerSymbol::X));
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(4, "token"),
+        })]
+    );
+}
+
+#[test]
+fn win_league_with_platform_fee() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate_with_fee(&mut deps, 5_000);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::CreateLeague {
+            entry_fee: coin(2, "token"),
+            max_players: 2,
+            start_time: mock_env().block.time,
+            double_round_robin: false,
+            points: None,
+            payout_split: vec![100],
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap();
+    for player in ["host", "opponent"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(player, &coins(2, "token")),
+            ExecuteMsg::JoinLeague { league_id: 1 },
+        )
+        .unwrap();
+    }
+    play_moves(
+        &mut deps,
+        1,
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves(&mut deps, 1, &[(true, (0, 2))]);
+
+    // THEN
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(2, "token"),
+        })]
+    );
+}
+
+#[test]
+fn rate_players_of_completed_game() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    complete_game(&mut deps);
+
+    // THEN
+    let rating: RatingResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Rating {
+                address: String::from("host"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(rating.rating, 1216);
+    assert_eq!(rating.games, 1);
+    let leaderboard: Vec<RatingResponse> = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Leaderboard {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        leaderboard
+            .iter()
+            .map(|rating| (rating.address.as_str(), rating.rating))
+            .collect::<Vec<_>>(),
+        vec![("host", 1216), ("opponent", 1184)]
+    );
+    let leaderboard: Vec<RatingResponse> = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Leaderboard {
+                start_after: Some(String::from("host")),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(leaderboard.len(), 1);
+    assert_eq!(leaderboard[0].address, Addr::unchecked("opponent"));
+}
+
+#[test]
+fn track_player_stats_of_completed_game() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+
+    // WHEN
+    complete_game(&mut deps);
+
+    // THEN
+    let host = query_player_stats(&deps, "host");
+    assert_eq!(host.games_played, 1);
+    assert_eq!(host.wins, 1);
+    assert_eq!(host.staked, coins(2, "token"));
+    assert_eq!(host.won, coins(4, "token"));
+    assert_eq!(host.longest_win_streak, 1);
+    let opponent = query_player_stats(&deps, "opponent");
+    assert_eq!(opponent.games_played, 1);
+    assert_eq!(opponent.losses, 1);
+    assert_eq!(opponent.staked, coins(2, "token"));
+    assert!(opponent.won.is_empty());
+    assert_eq!(opponent.win_streak, 0);
+}
+
+#[test]
+fn finalize_season_paying_top_players() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    create_season(&mut deps);
+    complete_game(&mut deps);
+    let ranking: SeasonRankingResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SeasonRanking {
+                season_id: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(ranking.season_id, 1);
+    assert_eq!(
+        ranking
+            .standings
+            .iter()
+            .map(|standing| (standing.player.as_str(), standing.points))
+            .collect::<Vec<_>>(),
+        vec![("host", 3), ("opponent", 0)]
+    );
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3600);
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("anyone", &[]),
+        ExecuteMsg::FinalizeSeason { season_id: 1 },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|message| message.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: coins(7, "token"),
+            }),
+            // The admin gets back the share of the runner-up, who never scored.
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: coins(3, "token"),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn finalize_season_before_end_time() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    create_season(&mut deps);
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::FinalizeSeason { season_id: 1 },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::SeasonNotEnded {
+            end_time: mock_env().block.time.plus_seconds(3600),
+        }
+    );
+}
+
+#[test]
+fn draw_against_optimal_house() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    fund_house(&mut deps, 100);
+    let res = play_house(&mut deps, (0, 0), Some(10)).unwrap();
+    assert_eq!(house_move(&res), (1, 1));
+    assert_eq!(query_house_bankroll(&deps), coins(90, "token"));
+    let res = play_house(&mut deps, (1, 0), None).unwrap();
+    assert_eq!(house_move(&res), (2, 0));
+    let res = play_house(&mut deps, (0, 2), None).unwrap();
+    assert_eq!(house_move(&res), (0, 1));
+    let res = play_house(&mut deps, (2, 1), None).unwrap();
+    let last_cell = if house_move(&res) == (1, 2) {
+        (2, 2)
+    } else {
+        (1, 2)
+    };
+
+    // WHEN
+    let res = play_house(&mut deps, last_cell, None).unwrap();
+
+    // THEN
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("player"),
+            amount: coins(10, "token"),
+        })
+    );
+    assert_eq!(query_house_bankroll(&deps), coins(100, "token"));
+    let game = query_games(&deps, None, None, None, None).remove(0);
+    assert_eq!(game.opponent, mock_env().contract.address);
+    assert_eq!(game.game.status, Status::COMPLETED);
+    assert_eq!(game.game.winner, None);
+    assert_eq!(
+        query_player_stats(&deps, "player").staked,
+        coins(10, "token")
+    );
+    assert_eq!(
+        query_player_stats(&deps, MOCK_CONTRACT_ADDR),
+        PlayerStats::default()
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("player", &coins(10, "token")),
+        ExecuteMsg::Rematch { game_id: GAME_ID },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+}
+
+#[test]
+fn play_house_move_as_player() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    fund_house(&mut deps, 100);
+    let res = play_house(&mut deps, (0, 0), Some(10)).unwrap();
+    assert_eq!(house_move(&res), (1, 1));
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("player", &[]),
+        ExecuteMsg::Play {
+            as_host: true,
+            coord: Coord {
+                x: 1,
+                y: 0,
+                z: None,
+            },
+            opponent: mock_env().contract.address.to_string(),
+            game_id: GAME_ID,
+            symbol: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::InvalidGame {
+            host: Addr::unchecked("player"),
+            opponent: mock_env().contract.address,
+        }
+    );
+    let res = play_house(&mut deps, (2, 2), None).unwrap();
+    assert_ne!(house_move(&res), (2, 0));
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.board[2][2], Some(PlayerSymbol::X));
+    assert_eq!(game.status, Status::PLAYING);
+}
+
+#[test]
+fn play_house_with_stake_over_bankroll_limit() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    fund_house(&mut deps, 100);
+
+    // WHEN
+    let err = play_house(&mut deps, (0, 0), Some(20)).unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::HouseStakeTooHigh {
+            max: Uint128::new(10)
+        }
+    );
+}
+
+#[test]
+fn play_game_staked_with_cw20() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    receive_cw20(
+        &mut deps,
+        "host",
+        ReceiveMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
+        },
+    );
+    receive_cw20(
+        &mut deps,
+        "opponent",
+        ReceiveMsg::Accept {
+            coord: Some(Coord {
+                x: 1,
+                y: 0,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    );
+    play_moves(&mut deps, GAME_ID, &[(true, (0, 1)), (false, (1, 1))]);
+
+    // WHEN
+    let res = play_moves(&mut deps, GAME_ID, &[(true, (0, 2))]);
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.token, Some(Addr::unchecked("cw20-token")));
+    assert_eq!(game.prize, coins(4, "cw20-token"));
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("cw20-token"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("host"),
+                amount: Uint128::new(4),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn accept_cw20_game_with_native_funds() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    receive_cw20(
+        &mut deps,
+        "host",
+        ReceiveMsg::Invite {
+            coord: None,
+            opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
+        },
+    );
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "cw20-token")),
+        ExecuteMsg::Accept {
+            coord: None,
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(err, ContractError::InvalidReceivedFunds {});
+}
+
+#[test]
+fn invite_outside_stake_limits() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    limit_token_stakes(&mut deps);
+    let invite_with = |deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+                       funds: &[Coin]| {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", funds),
+            ExecuteMsg::Invite {
+                coord: None,
+                opponent: String::from("opponent"),
+                ruleset: None,
+                first_player: None,
+                commitment: None,
+            },
+        )
+        .unwrap_err()
+    };
+
+    // WHEN
+    let too_low = invite_with(&mut deps, &coins(1, "token"));
+    let too_high = invite_with(&mut deps, &coins(11, "token"));
+    let not_allowed = invite_with(&mut deps, &coins(2, "other"));
+    let multiple = invite_with(&mut deps, &[coin(2, "token"), coin(2, "other")]);
+    let empty = invite_with(&mut deps, &[]);
+
+    // THEN
+    assert_eq!(
+        too_low,
+        ContractError::StakeTooLow {
+            min: coin(2, "token")
+        }
+    );
+    assert_eq!(
+        too_high,
+        ContractError::StakeTooHigh {
+            max: coin(10, "token")
+        }
+    );
+    assert_eq!(
+        not_allowed,
+        ContractError::StakeDenomNotAllowed {
+            denom: String::from("other")
+        }
+    );
+    assert_eq!(multiple, ContractError::MultipleStakeDenoms {});
+    assert_eq!(empty, ContractError::StakeRequired {});
+}
+
+#[test]
+fn create_tournament_outside_stake_limits() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    limit_token_stakes(&mut deps);
+
+    // WHEN
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::CreateTournament {
+            entry_fee: coin(11, "token"),
+            max_players: 4,
+            start_time: mock_env().block.time,
+            payout_split: vec![100],
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::StakeTooHigh {
+            max: coin(10, "token")
+        }
+    );
+}
+
+#[test]
+fn join_tournament_with_split_entry_fee() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    create_tournament(&mut deps, 2);
+
+    // WHEN
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "host",
+            &[coin(1, "token"), coin(1, "token"), coin(0, "other")],
+        ),
+        ExecuteMsg::JoinTournament { tournament_id: 1 },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        query_tournament(&deps).players,
+        vec![Addr::unchecked("host")]
+    );
+}
+
+#[test]
+fn accept_with_split_funds() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+
+    // WHEN
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "opponent",
+            &[coin(1, "token"), coin(0, "other"), coin(1, "token")],
+        ),
+        ExecuteMsg::Accept {
+            coord: Some(Coord {
+                x: 1,
+                y: 0,
+                z: None,
+            }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+
+    // THEN
+    let game = query_games(&deps, None, None, None, None).remove(0).game;
+    assert_eq!(game.status, Status::PLAYING);
+    assert_eq!(game.prize, coins(4, "token"));
+}
+
+#[test]
+fn withdraw_platform_fee_of_won_game() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg {
+            max_open_games_per_pair: None,
+            reveal_timeout: None,
+            treasury: Some(String::from("treasury")),
+            fee_bps: Some(2_500),
+        },
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+    accept_invite(&mut deps, (1, 0));
+    play_moves(&mut deps, GAME_ID, &[(true, (0, 1)), (false, (1, 1))]);
+    let res = play_moves(&mut deps, GAME_ID, &[(true, (0, 2))]);
+
+    // WHEN
+    let withdrawal = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("treasury", &[]),
+        ExecuteMsg::WithdrawFees {},
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(3, "token"),
+        })]
+    );
+    assert_eq!(
+        withdrawal.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("treasury"),
+            amount: coins(1, "token"),
+        })]
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Fees {}).unwrap();
+    let fees: FeesResponse = from_binary(&res).unwrap();
+    assert!(fees.native.is_empty());
+}
+
+#[test]
+fn reject_without_platform_fee() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg {
+            max_open_games_per_pair: None,
+            reveal_timeout: None,
+            treasury: None,
+            fee_bps: Some(2_500),
+        },
+    )
+    .unwrap();
+    invite(&mut deps, "host", "opponent");
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Reject {
+            as_host: false,
+            opponent: String::from("host"),
+            game_id: GAME_ID,
+        },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: coins(2, "token"),
+        })]
+    );
+}
+
+#[test]
+fn draw_refunds_stakes_with_platform_fee() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg {
+            max_open_games_per_pair: None,
+            reveal_timeout: None,
+            treasury: None,
+            fee_bps: Some(5_000),
+        },
+    )
+    .unwrap();
+    for (player, msg) in [
+        (
+            "host",
+            ExecuteMsg::Invite {
+                coord: Some(Coord {
+                    x: 0,
+                    y: 0,
+                    z: None,
+                }),
+                opponent: String::from("opponent"),
+                ruleset: None,
+                first_player: None,
+                commitment: None,
+            },
+        ),
+        (
+            "opponent",
+            ExecuteMsg::Accept {
+                coord: Some(Coord {
+                    x: 1,
+                    y: 0,
+                    z: None,
+                }),
+                host: String::from("host"),
+                game_id: GAME_ID,
+                nonce: None,
+            },
+        ),
+    ] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(player, &coins(3, "token")),
+            msg,
+        )
+        .unwrap();
+    }
+    play_moves(
+        &mut deps,
+        GAME_ID,
+        &[
+            (true, (2, 0)),
+            (false, (1, 1)),
+            (true, (1, 2)),
+            (false, (2, 1)),
+            (true, (0, 1)),
+            (false, (0, 2)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves(&mut deps, GAME_ID, &[(true, (2, 2))]);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(2, "token"));
+
+    // THEN
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: coins(2, "token"),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("opponent"),
+                amount: coins(2, "token"),
+            }),
+        ]
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EscrowBalance {}).unwrap();
+    let escrow: EscrowBalanceResponse = from_binary(&res).unwrap();
+    assert_eq!(escrow.escrowed, coins(2, "token"));
+    assert!(escrow.surplus.is_empty());
+    assert!(escrow.shortfall.is_empty());
+}
+
+#[test]
+fn draw_refunds_of_game_without_stakes() {
+    // GIVEN
+    let mut game = Game::new(
+        Addr::unchecked("host"),
+        Addr::unchecked("opponent"),
+        Ruleset::default(),
+        FirstPlayer::Host,
+        None,
+        coins(5, "token"),
+    );
+
+    // WHEN
+    game.host_stake = vec![];
+
+    // THEN
+    assert_eq!(game.draw_refunds(), (coins(3, "token"), coins(2, "token")));
+}
+
+#[test]
+fn draw_refunds_of_game_invited_without_stakes() {
+    // GIVEN
+    let mut game = Game::new(
+        Addr::unchecked("host"),
+        Addr::unchecked("opponent"),
+        Ruleset::default(),
+        FirstPlayer::Host,
+        None,
+        coins(3, "token"),
+    );
+    game.host_stake = vec![];
+
+    // WHEN
+    game.add_opponent_stake(coins(3, "token"));
+
+    // THEN
+    assert_eq!(game.draw_refunds(), (coins(3, "token"), coins(3, "token")));
+}
+
+#[test]
+fn play_game_wagering_nfts() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    receive_nft(&mut deps, "punks", "host", "1", nft_invite()).unwrap();
+    receive_nft(
+        &mut deps,
+        "apes",
+        "opponent",
+        "7",
+        ReceiveNftMsg::Accept {
+            coord: None,
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+    play_moves(
+        &mut deps,
+        GAME_ID,
+        &[
+            (true, (0, 0)),
+            (false, (1, 0)),
+            (true, (0, 1)),
+            (false, (1, 1)),
+        ],
+    );
+
+    // WHEN
+    let res = play_moves(&mut deps, GAME_ID, &[(true, (0, 2))]);
+
+    // THEN
+    let transfer = |collection: &str, token_id: &str| {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from(collection),
+            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: String::from("host"),
+                token_id: String::from(token_id),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+    assert_eq!(
+        res.messages,
+        vec![transfer("punks", "1"), transfer("apes", "7")]
+    );
+}
+
+#[test]
+fn nft_invite_within_stake_limits() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    limit_token_stakes(&mut deps);
+    let mut too_high = nft_invite();
+    if let ReceiveNftMsg::Invite { native_value, .. } = &mut too_high {
+        *native_value = Some(coin(11, "token"));
+    }
+
+    // WHEN
+    let res = receive_nft(&mut deps, "punks", "host", "1", nft_invite());
+    let err = receive_nft(&mut deps, "punks", "player", "2", too_high).unwrap_err();
+
+    // THEN
+    assert!(res.is_ok());
+    assert_eq!(
+        err,
+        ContractError::StakeTooHigh {
+            max: coin(10, "token")
+        }
+    );
+}
+
+#[test]
+fn accept_nft_wager_from_other_collection() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    receive_nft(&mut deps, "punks", "host", "1", nft_invite()).unwrap();
+
+    // WHEN
+    let err = receive_nft(
+        &mut deps,
+        "kitties",
+        "opponent",
+        "3",
+        ReceiveNftMsg::Accept {
+            coord: None,
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap_err();
+
+    // THEN
+    assert_eq!(
+        err,
+        ContractError::InvalidNftWager {
+            reason: String::from("the token must belong to an allowed collection")
+        }
+    );
+}
+
+#[test]
+fn reject_nft_wager() {
+    // GIVEN
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        InstantiateMsg::default(),
+    )
+    .unwrap();
+    receive_nft(&mut deps, "punks", "host", "1", nft_invite()).unwrap();
+
+    // WHEN
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        ExecuteMsg::Reject {
+            as_host: false,
+            opponent: String::from("host"),
+            game_id: GAME_ID,
+        },
+    )
+    .unwrap();
+
+    // THEN
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("punks"),
+            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: String::from("host"),
+                token_id: String::from("1"),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+// The host wins with the first column.
+fn complete_game(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>) {
+    invite(deps, "host", "opponent");
+    accept_invite(deps, (1, 0));
+    play(deps, "host", true, (0, 1));
+    play(deps, "opponent", false, (1, 1));
+    play(deps, "host", true, (0, 2));
+}
+
+fn instantiate_with_fee(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, fee_bps: u32) {
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        InstantiateMsg {
+            max_open_games_per_pair: None,
+            reveal_timeout: None,
+            treasury: None,
+            fee_bps: Some(fee_bps),
+        },
+    )
+    .unwrap();
+}
+
+fn start_series(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, best_of: u8) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::CreateSeries {
+            opponent: String::from("opponent"),
+            best_of,
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::AcceptSeries { series_id: 1 },
+    )
+    .unwrap();
+}
+
+// Plays the moves in order, returning the response of the last one.
+fn play_moves(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    game_id: u64,
+    moves: &[(bool, (u8, u8))],
+) -> Response {
+    play_moves_between(deps, game_id, ("host", "opponent"), moves)
+}
+
+fn play_moves_between(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    game_id: u64,
+    (host, opponent): (&str, &str),
+    moves: &[(bool, (u8, u8))],
+) -> Response {
+    let mut res = Response::new();
+    for (as_host, (x, y)) in moves.iter().copied() {
+        res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(if as_host { host } else { opponent }, &[]),
+            ExecuteMsg::Play {
+                as_host,
+                coord: Coord { x, y, z: None },
+                opponent: String::from(if as_host { opponent } else { host }),
+                game_id,
+                symbol: None,
+            },
+        )
+        .unwrap();
+    }
+    res
+}
+
+fn create_tournament(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, max_players: u32) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("organizer", &[]),
+        ExecuteMsg::CreateTournament {
+            entry_fee: coin(2, "token"),
+            max_players,
+            start_time: mock_env().block.time.plus_seconds(3600),
+            payout_split: vec![70, 30],
+            ruleset: None,
+            move_timeout: None,
+        },
+    )
+    .unwrap();
+}
+
+fn join_tournament(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, player: &str) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(player, &coins(2, "token")),
+        ExecuteMsg::JoinTournament { tournament_id: 1 },
+    )
+    .unwrap();
+}
+
+fn query_tournament(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>) -> Tournament {
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Tournament { id: 1 }).unwrap();
+    from_binary::<TournamentResponse>(&res).unwrap().tournament
+}
+
+fn query_player_stats(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    address: &str,
+) -> PlayerStats {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PlayerStats {
+            address: String::from(address),
+        },
+    )
+    .unwrap();
+    from_binary::<PlayerStatsResponse>(&res).unwrap().stats
+}
+
+fn create_season(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(10, "token")),
+        ExecuteMsg::CreateSeason {
+            start_time: mock_env().block.time,
+            end_time: mock_env().block.time.plus_seconds(3600),
+            payout_split: vec![70, 30],
+            points: None,
+        },
+    )
+    .unwrap();
+}
+
+fn fund_house(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, amount: u128) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(amount, "token")),
+        ExecuteMsg::FundHouse {},
+    )
+    .unwrap();
+}
+
+fn play_house(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    (x, y): (u8, u8),
+    stake: Option<u128>,
+) -> Result<Response, ContractError> {
+    let funds = stake.map(|stake| coins(stake, "token")).unwrap_or_default();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("player", &funds),
+        ExecuteMsg::PlayHouse {
+            coord: Coord { x, y, z: None },
+            stake: stake.map(|stake| coin(stake, "token")),
+        },
+    )
+}
+
+fn house_move(res: &Response) -> (u8, u8) {
+    let attribute = |key: &str| {
+        res.attributes
+            .iter()
+            .find(|attribute| attribute.key == key)
+            .unwrap()
+            .value
+            .parse()
+            .unwrap()
+    };
+    (attribute("house_x"), attribute("house_y"))
+}
+
+fn query_house_bankroll(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>) -> Vec<Coin> {
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::HouseBankroll {}).unwrap();
+    from_binary(&res).unwrap()
+}
+
+// Sends 2 tokens of the "cw20-token" contract from the player with the message.
+fn receive_cw20(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    player: &str,
+    msg: ReceiveMsg,
+) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cw20-token", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(player),
+            amount: Uint128::new(2),
+            msg: to_binary(&msg).unwrap(),
+        }),
+    )
+    .unwrap();
+}
+
+// Sends the token of the collection from the player with the message.
+fn receive_nft(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    collection: &str,
+    player: &str,
+    token_id: &str,
+    msg: ReceiveNftMsg,
+) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(collection, &[]),
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: String::from(player),
+            token_id: String::from(token_id),
+            msg: to_binary(&msg).unwrap(),
+        }),
+    )
+}
+
+// Invites the opponent to wager a token of the "apes" collection or 5 token.
+fn nft_invite() -> ReceiveNftMsg {
+    ReceiveNftMsg::Invite {
+        coord: None,
+        opponent: String::from("opponent"),
+        ruleset: None,
+        first_player: None,
+        commitment: None,
+        allowed_collections: vec![String::from("apes")],
+        native_value: Some(coin(5, "token")),
+    }
+}
+
+fn auto_match(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, player: &str) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(player, &coins(2, "token")),
+        ExecuteMsg::AutoMatch {
+            stake: coin(2, "token"),
+        },
+    )
+    .unwrap();
+}
+
+fn query_lobby(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    denom: Option<&str>,
+    min_stake: Option<u128>,
+) -> Vec<OpenInviteResponse> {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Lobby {
+            denom: denom.map(String::from),
+            min_stake: min_stake.map(Uint128::new),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn invite(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, host: &str, opponent: &str) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(host, &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            opponent: String::from(opponent),
+            ruleset: None,
+            first_player: None,
+            commitment: None,
+        },
+    )
+    .unwrap();
+}
+
+fn query_games(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    status: Option<Status>,
+    player: Option<&str>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Vec<GameResponse> {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Games {
+            status,
+            player: player.map(String::from),
+            start_after,
+            limit,
+        },
+    );
+    from_binary(&res.unwrap()).unwrap()
+}
+
+fn game_ids(games: &[GameResponse]) -> Vec<u64> {
+    games.iter().map(|game| game.game_id).collect()
+}
+
+fn query_player_games(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    address: &str,
+    role: Option<Role>,
+    status: Option<Status>,
+    start_after: Option<u64>,
+) -> PlayerGamesResponse {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PlayerGames {
+            address: String::from(address),
+            role,
+            status,
+            start_after,
+            limit: None,
+        },
+    );
+    from_binary(&res.unwrap()).unwrap()
+}
+
+fn query_active_games(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    player: &str,
+    opponent: &str,
+) -> Vec<u64> {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ActiveGames {
+            player: String::from(player),
+            opponent: String::from(opponent),
+        },
+    );
+    from_binary(&res.unwrap()).unwrap()
+}
+
+fn play(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    sender: &str,
+    as_host: bool,
+    (x, y): (u8, u8),
+) -> Response {
+    let opponent = if as_host { "opponent" } else { "host" };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(sender, &[]),
+        ExecuteMsg::Play {
+            as_host,
+            coord: Coord { x, y, z: None },
+            opponent: String::from(opponent),
+            game_id: GAME_ID,
+            symbol: None,
+        },
+    )
+    .unwrap()
+}
+
+fn invite_ultimate(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, coord: Coord) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(coord),
+            opponent: String::from("opponent"),
+            ruleset: Some(Ruleset {
+                variant: Variant::Ultimate,
+                ..Ruleset::default()
+            }),
+            first_player: None,
+            commitment: None,
+        },
+    )
+    .unwrap();
+}
+
+fn qubic_ruleset() -> Ruleset {
+    Ruleset {
+        variant: Variant::Qubic,
+        board_size: 4,
+        win_length: 4,
+        ..Ruleset::default()
+    }
+}
+
+fn invite_with_ruleset(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    ruleset: Ruleset,
+) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: None,
+            }),
+            opponent: String::from("opponent"),
+            ruleset: Some(ruleset),
+            first_player: None,
+            commitment: None,
+        },
+    )
+    .unwrap();
+}
+
+fn accept_invite(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, (x, y): (u8, u8)) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: Some(Coord { x, y, z: None }),
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: None,
+        },
+    )
+    .unwrap();
+}
+
+// The first move is the one of the opponent accepting the invite.
+fn start_notakto(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    moves: &[(u8, u8, u8)],
+) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: Some(Coord {
+                x: 0,
+                y: 0,
+                z: Some(0),
+            }),
+            opponent: String::from("opponent"),
+            ruleset: Some(Ruleset {
+                variant: Variant::Notakto,
+                boards: 2,
+                ..Ruleset::default()
+            }),
+            first_player: None,
+            commitment: None,
+        },
+    )
+    .unwrap();
+    for (round, (x, y, z)) in moves.iter().copied().enumerate() {
+        let coord = Coord { x, y, z: Some(z) };
+        if round == 0 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("opponent", &coins(2, "token")),
+                ExecuteMsg::Accept {
+                    coord: Some(coord),
+                    host: String::from("host"),
+                    game_id: GAME_ID,
+                    nonce: None,
+                },
+            )
+            .unwrap();
+            continue;
+        }
+
+        let as_host = round % 2 == 1;
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(if as_host { "host" } else { "opponent" }, &[]),
+            ExecuteMsg::Play {
+                as_host,
+                coord,
+                opponent: String::from(if as_host { "opponent" } else { "host" }),
+                game_id: GAME_ID,
+                symbol: None,
+            },
+        )
+        .unwrap();
+    }
+}
+
+fn invite_first(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    first_player: FirstPlayer,
+) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: None,
+            opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: Some(first_player),
+            commitment: None,
+        },
+    )
+    .unwrap();
+}
+
+fn start_coin_flip(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    secret: &[u8],
+    nonce: &[u8],
+) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(2, "token")),
+        ExecuteMsg::Invite {
+            coord: None,
+            opponent: String::from("opponent"),
+            ruleset: None,
+            first_player: Some(FirstPlayer::Random),
+            commitment: Some(Binary::from(Sha256::digest(secret).to_vec())),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(2, "token")),
+        ExecuteMsg::Accept {
+            coord: None,
+            host: String::from("host"),
+            game_id: GAME_ID,
+            nonce: Some(Binary::from(nonce)),
+        },
+    )
+    .unwrap();
+}
+
+// Lets the admin limit stakes to between 2 and 10 token.
+fn limit_token_stakes(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            max_open_games_per_pair: None,
+            reveal_timeout: None,
+            house: None,
+            treasury: None,
+            fee_bps: None,
+            stake_limits: Some(vec![StakeLimit {
+                denom: String::from("token"),
+                min: Uint128::new(2),
+                max: Some(Uint128::new(10)),
+            }]),
+        },
+    )
+    .unwrap();
+}